@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+use crate::metrics::{EcoImpactScalar, RiskScalar};
+
+/// Latest neurorights-relevant readings for an organically-integrated interface.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NeuroRightsSnapshot {
+    /// Bioscale risk index, 0..1.
+    pub normalized_risk: RiskScalar,
+    /// Eco-impact per joule of the human-coupled channel, 0..1.
+    pub eco_impact_index: EcoImpactScalar,
+}
+
+/// Static description of how a node couples to a human host.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BioIntegrationProfile {
+    /// True if any reward signal correlates with distress or coercion markers.
+    pub reward_couples_to_distress: bool,
+}
@@ -0,0 +1,184 @@
+use serde::{Deserialize, Serialize};
+
+use crate::infra::InfraNodeShardSnapshot;
+use crate::metrics::RiskScalar;
+
+/// Which side of a limit is safe (K.1.1).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Direction {
+    /// Safe while the value stays at or below the limit (e.g. NOx).
+    Max,
+    /// Safe while the value stays at or above the limit (e.g. residence time).
+    Min,
+}
+
+/// Corridor specification for one safety-critical parameter (K.1.2, shard type 1).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CorridorBand {
+    pub param_name: String,
+    pub unit: String,
+    pub direction: Direction,
+    pub r_min: f64,
+    pub r_max: f64,
+    pub weight_w: f64,
+    pub channel: u32,
+    #[serde(default)]
+    pub legal_limit: Option<f64>,
+    #[serde(default)]
+    pub gold_limit: Option<f64>,
+}
+
+impl CorridorBand {
+    /// Unclipped risk coordinate; values above 1 are past the corridor edge.
+    pub fn raw_risk(&self, x: f64) -> f64 {
+        let span = self.r_max - self.r_min;
+        match self.direction {
+            Direction::Max => (x - self.r_min) / span,
+            Direction::Min => (self.r_max - x) / span,
+        }
+    }
+
+    /// Normalized risk coordinate `r_x = clip[0,1](..)` per K.1.2.
+    pub fn risk(&self, x: f64) -> RiskScalar {
+        self.raw_risk(x).clamp(0.0, 1.0)
+    }
+
+    /// Reading whose risk coordinate is `r`, the inverse of `risk` on [0,1].
+    pub fn value_at_risk(&self, r: RiskScalar) -> f64 {
+        let span = self.r_max - self.r_min;
+        match self.direction {
+            Direction::Max => self.r_min + r * span,
+            Direction::Min => self.r_max - r * span,
+        }
+    }
+
+    /// `r_x <= 1`; a non-finite reading is never inside the corridor.
+    pub fn within_corridor(&self, x: f64) -> bool {
+        x.is_finite() && self.raw_risk(x) <= 1.0
+    }
+
+    fn meets(&self, x: f64, limit: Option<f64>) -> bool {
+        match (limit, self.direction) {
+            (None, _) => true,
+            (Some(l), Direction::Max) => x <= l,
+            (Some(l), Direction::Min) => x >= l,
+        }
+    }
+
+    /// Regulatory limit holds (E.3); parameters without one always pass.
+    pub fn legal_ok(&self, x: f64) -> bool {
+        self.meets(x, self.legal_limit)
+    }
+
+    /// Gold (health/LCA) limit holds (E.3); parameters without one always pass.
+    pub fn gold_ok(&self, x: f64) -> bool {
+        self.meets(x, self.gold_limit)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CorridorResult {
+    Ok,
+    Violation(String),
+}
+
+/// Corridor and legal-limit check over every channel of a snapshot.
+///
+/// Fails on malformed bands (`r_max == r_min`), non-finite readings, readings
+/// past the corridor edge, and readings beyond the legal limit.
+pub fn check_corridors(snap: &InfraNodeShardSnapshot) -> CorridorResult {
+    let mut faults = Vec::new();
+    for ch in &snap.channels {
+        let band = &ch.band;
+        if !(band.r_max - band.r_min).is_normal() {
+            faults.push(format!("{}: degenerate corridor [{}, {}]", band.param_name, band.r_min, band.r_max));
+        } else if !band.within_corridor(ch.value) {
+            faults.push(format!(
+                "{}={} {} outside corridor (r={:.3})",
+                band.param_name,
+                ch.value,
+                band.unit,
+                band.raw_risk(ch.value)
+            ));
+        } else if !band.legal_ok(ch.value) {
+            faults.push(format!(
+                "{}={} {} breaches legal limit {}",
+                band.param_name,
+                ch.value,
+                band.unit,
+                band.legal_limit.unwrap_or_default()
+            ));
+        }
+    }
+    if faults.is_empty() {
+        CorridorResult::Ok
+    } else {
+        CorridorResult::Violation(format!("node {}: {}", snap.node_id, faults.join("; ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::{InfraNodeShardId, ShardChannel};
+
+    fn nox_band() -> CorridorBand {
+        CorridorBand {
+            param_name: "NOx_stack".into(),
+            unit: "mg/Nm3".into(),
+            direction: Direction::Max,
+            r_min: 0.0,
+            r_max: 150.0,
+            weight_w: 0.22,
+            channel: 0,
+            legal_limit: Some(150.0),
+            gold_limit: Some(40.0),
+        }
+    }
+
+    #[test]
+    fn test_max_and_min_normalization() {
+        let nox = nox_band();
+        assert!((nox.risk(95.0) - 95.0 / 150.0).abs() < 1e-12);
+        assert_eq!(nox.risk(-5.0), 0.0);
+        assert_eq!(nox.risk(200.0), 1.0);
+        assert!(!nox.within_corridor(200.0));
+        assert!(nox.legal_ok(95.0) && !nox.gold_ok(95.0));
+
+        let residence = CorridorBand {
+            param_name: "residence_time".into(),
+            unit: "s".into(),
+            direction: Direction::Min,
+            r_min: 2.0,
+            r_max: 4.0,
+            weight_w: 0.1,
+            channel: 1,
+            legal_limit: Some(2.0),
+            gold_limit: None,
+        };
+        assert_eq!(residence.risk(4.5), 0.0);
+        assert!((residence.risk(3.0) - 0.5).abs() < 1e-12);
+        assert!(residence.within_corridor(2.0));
+        assert!(!residence.within_corridor(1.5));
+        assert!(!residence.legal_ok(1.5));
+        assert_eq!(residence.value_at_risk(0.5), 3.0);
+        assert_eq!(nox.value_at_risk(nox.risk(95.0)), 95.0);
+    }
+
+    #[test]
+    fn test_check_corridors() {
+        let mut snap = InfraNodeShardSnapshot {
+            node_id: InfraNodeShardId::from("PHX-CYBO-1"),
+            timestamp: "2026-01-17T19:27:00Z".into(),
+            channels: vec![ShardChannel { band: nox_band(), value: 95.0 }],
+            controls: Vec::new(),
+        };
+        assert_eq!(check_corridors(&snap), CorridorResult::Ok);
+
+        snap.channels[0].value = 151.0;
+        assert!(matches!(check_corridors(&snap), CorridorResult::Violation(m) if m.contains("NOx_stack")));
+        snap.channels[0].value = f64::NAN;
+        assert!(matches!(check_corridors(&snap), CorridorResult::Violation(_)));
+    }
+}
@@ -0,0 +1,165 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::corridor::CorridorBand;
+use crate::lyapunov::{GlobalResidual, ResidualTerm};
+use crate::metrics::RiskScalar;
+
+/// Identifier of one infrastructure node shard (e.g. `PHX-CYBO-1`).
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct InfraNodeShardId(pub String);
+
+impl fmt::Display for InfraNodeShardId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for InfraNodeShardId {
+    fn from(s: &str) -> Self {
+        InfraNodeShardId(s.to_string())
+    }
+}
+
+/// One measured safety parameter together with its corridor band.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ShardChannel {
+    pub band: CorridorBand,
+    pub value: f64,
+}
+
+impl ShardChannel {
+    pub fn risk(&self) -> RiskScalar {
+        self.band.risk(self.value)
+    }
+}
+
+/// One actuator with its physical range; MPC controls are normalized onto `[min, max]`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ControlChannel {
+    pub name: String,
+    pub unit: String,
+    pub min: f64,
+    pub max: f64,
+    pub current: f64,
+}
+
+impl ControlChannel {
+    pub fn normalized(&self) -> f64 {
+        let span = self.max - self.min;
+        if span > 0.0 { ((self.current - self.min) / span).clamp(0.0, 1.0) } else { 0.0 }
+    }
+
+    pub fn denormalize(&self, u: f64) -> f64 {
+        self.min + u.clamp(0.0, 1.0) * (self.max - self.min)
+    }
+}
+
+/// Point-in-time view of a node: measured channels and current actuator settings.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InfraNodeShardSnapshot {
+    pub node_id: InfraNodeShardId,
+    pub timestamp: String,
+    pub channels: Vec<ShardChannel>,
+    #[serde(default)]
+    pub controls: Vec<ControlChannel>,
+}
+
+/// Maps a snapshot onto the normalized vectors the MPC kernel works in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ShardStateExtractor;
+
+impl ShardStateExtractor {
+    /// Risk coordinates of every channel, in channel order, each in [0,1].
+    pub fn state_vector(&self, snap: &InfraNodeShardSnapshot) -> Vec<f64> {
+        snap.channels.iter().map(ShardChannel::risk).collect()
+    }
+
+    /// Current actuator settings, each normalized into [0,1].
+    pub fn control_vector(&self, snap: &InfraNodeShardSnapshot) -> Vec<f64> {
+        snap.controls.iter().map(ControlChannel::normalized).collect()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ControlSetpoint {
+    pub name: String,
+    pub unit: String,
+    pub value: f64,
+}
+
+/// Physical setpoints to apply to a node.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InfraControlCommand {
+    pub node_id: InfraNodeShardId,
+    pub setpoints: Vec<ControlSetpoint>,
+}
+
+impl InfraNodeShardSnapshot {
+    pub fn mpc_state_extractor(&self) -> ShardStateExtractor {
+        ShardStateExtractor
+    }
+
+    /// Turn a normalized control vector into physical setpoints.
+    ///
+    /// Entries are clamped to [0,1] before mapping; actuators without a matching
+    /// entry keep their current setting.
+    pub fn control_from_mpc<U: AsRef<[f64]> + ?Sized>(&self, u: &U) -> InfraControlCommand {
+        let u = u.as_ref();
+        let setpoints = self
+            .controls
+            .iter()
+            .enumerate()
+            .map(|(i, c)| ControlSetpoint {
+                name: c.name.clone(),
+                unit: c.unit.clone(),
+                value: u.get(i).map_or(c.current, |&v| c.denormalize(v)),
+            })
+            .collect();
+        InfraControlCommand { node_id: self.node_id.clone(), setpoints }
+    }
+
+    /// This node's contribution to the fleet residual.
+    pub fn residual_terms(&self) -> Vec<ResidualTerm> {
+        self.channels
+            .iter()
+            .map(|ch| ResidualTerm {
+                node_id: self.node_id.clone(),
+                channel: ch.band.channel,
+                r: ch.risk(),
+                w: ch.band.weight_w,
+                within_corridor: ch.band.within_corridor(ch.value),
+            })
+            .collect()
+    }
+
+    pub fn residual(&self) -> GlobalResidual {
+        GlobalResidual { terms: self.residual_terms() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_control_from_mpc_maps_and_clamps() {
+        let snap = InfraNodeShardSnapshot {
+            node_id: "PHX-MAR-01".into(),
+            timestamp: "2026-06-01T12:00:00Z".into(),
+            channels: Vec::new(),
+            controls: vec![
+                ControlChannel { name: "pump_rpm".into(), unit: "rpm".into(), min: 0.0, max: 3000.0, current: 1500.0 },
+                ControlChannel { name: "valve".into(), unit: "%".into(), min: 10.0, max: 90.0, current: 50.0 },
+                ControlChannel { name: "fan".into(), unit: "%".into(), min: 0.0, max: 100.0, current: 20.0 },
+            ],
+        };
+        assert_eq!(snap.mpc_state_extractor().control_vector(&snap), vec![0.5, 0.5, 0.2]);
+
+        let cmd = snap.control_from_mpc(&[0.25, 1.5][..]);
+        let values: Vec<f64> = cmd.setpoints.iter().map(|s| s.value).collect();
+        assert_eq!(values, vec![750.0, 90.0, 20.0]);
+        assert_eq!(cmd.node_id.to_string(), "PHX-MAR-01");
+    }
+}
@@ -0,0 +1,14 @@
+//! Shared K/E/R contracts (docs/K_E_R_Grammar.md) for the MPC crates: corridor
+//! bands and risk coordinates (K.1.2), the Lyapunov residual (K.1.3, E.2) and the
+//! infra-node shard types the runners read and command.
+
+pub mod metrics;
+pub mod bioscale;
+pub mod corridor;
+pub mod lyapunov;
+pub mod infra;
+
+pub use corridor::{check_corridors, CorridorBand, CorridorResult, Direction};
+pub use infra::{InfraControlCommand, InfraNodeShardId, InfraNodeShardSnapshot};
+pub use lyapunov::{GlobalResidual, ResidualComputer, WeightedSumResidual};
+pub use metrics::{EcoImpactScalar, KerVector, RiskScalar};
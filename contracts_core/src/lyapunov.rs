@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+
+use crate::infra::{InfraNodeShardId, InfraNodeShardSnapshot};
+use crate::metrics::RiskScalar;
+
+/// One weighted risk coordinate `w_j * r_j` of the residual (K.1.3).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ResidualTerm {
+    pub node_id: InfraNodeShardId,
+    pub channel: u32,
+    /// Clipped risk coordinate in [0,1].
+    pub r: RiskScalar,
+    pub w: f64,
+    /// False once the underlying reading is past the corridor edge (`r > 1` before clipping).
+    pub within_corridor: bool,
+}
+
+/// Risk coordinates of every channel across the fleet at one step.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GlobalResidual {
+    pub terms: Vec<ResidualTerm>,
+}
+
+impl GlobalResidual {
+    pub fn from_snapshots<'a>(snaps: impl IntoIterator<Item = &'a InfraNodeShardSnapshot>) -> Self {
+        GlobalResidual { terms: snaps.into_iter().flat_map(|s| s.residual_terms()).collect() }
+    }
+
+    /// R.1 `corridor_ok`: every coordinate is inside its corridor.
+    pub fn corridor_ok(&self) -> bool {
+        self.terms.iter().all(|t| t.within_corridor)
+    }
+}
+
+/// Scalar Lyapunov function over a [`GlobalResidual`].
+pub trait ResidualComputer {
+    fn value(&self, resid: &GlobalResidual) -> f64;
+
+    /// E.2 admissibility: `after` stays inside every corridor and
+    /// `V_{t+1} <= V_t + epsilon`.
+    fn is_admissible(&self, before: &GlobalResidual, after: &GlobalResidual, epsilon: f64) -> bool {
+        after.corridor_ok() && self.value(after) <= self.value(before) + epsilon
+    }
+}
+
+/// `V_t = sum_j w_j r_j` (K.1.3).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WeightedSumResidual;
+
+impl ResidualComputer for WeightedSumResidual {
+    fn value(&self, resid: &GlobalResidual) -> f64 {
+        resid.terms.iter().map(|t| t.w * t.r).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(channel: u32, r: f64, w: f64) -> ResidualTerm {
+        ResidualTerm { node_id: "n".into(), channel, r, w, within_corridor: r < 1.0 }
+    }
+
+    #[test]
+    fn test_weighted_sum_and_admissibility() {
+        let before = GlobalResidual { terms: vec![term(0, 0.63, 0.22), term(1, 0.5, 0.1)] };
+        let v = WeightedSumResidual.value(&before);
+        assert!((v - (0.63 * 0.22 + 0.05)).abs() < 1e-12);
+
+        let better = GlobalResidual { terms: vec![term(0, 0.5, 0.22), term(1, 0.5, 0.1)] };
+        assert!(WeightedSumResidual.is_admissible(&before, &better, 0.0));
+        assert!(!WeightedSumResidual.is_admissible(&better, &before, 0.0));
+        assert!(WeightedSumResidual.is_admissible(&better, &before, 0.05));
+
+        let mut breach = better.clone();
+        breach.terms[1].within_corridor = false;
+        assert!(!WeightedSumResidual.is_admissible(&before, &breach, 1.0));
+    }
+}
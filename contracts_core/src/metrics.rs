@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+/// Normalized risk in [0,1]; 0 is ideal, 1 is a corridor boundary (K.1.2).
+pub type RiskScalar = f64;
+
+/// Normalized eco-impact in [0,1]; higher is better.
+pub type EcoImpactScalar = f64;
+
+/// Per-step cost metrics a node reports for the MPC objective.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct KerVector {
+    /// Exergy (or kWh) spent per unit of useful work.
+    pub exergy_cost: f64,
+    /// Hardware or tissue degradation proxy, 0..1.
+    pub degradation_index: f64,
+    /// Fraction of the node's units that are powered, 0..1.
+    pub active_node_fraction: f64,
+    /// Fraction of requests or deliveries that missed their SLA, 0..1.
+    pub sla_violation_ratio: f64,
+}
@@ -1,22 +1,212 @@
 use serde::{Deserialize, Serialize};
-use contracts_core::infra::InfraNodeShardSnapshot;
-use contracts_core::corridor::CorridorResult;
+use contracts_core::infra::{InfraNodeShardSnapshot, ShardChannel};
+use contracts_core::corridor::{CorridorBand, Direction};
 
 /// Wraps existing corridorpresent-style checks for reuse in runners.[file:39]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CorridorCheck;
 
-#[derive(Debug, thiserror::Error)]
+/// A channel past its hard band, with the reading and the limit it crossed in
+/// the channel's own units.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, thiserror::Error)]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub enum CorridorViolation {
-    #[error("local corridor violation: {0}")]
-    Local(String),
+    /// Inside the corridor but beyond the legal limit.
+    #[error("channel {channel} ({param}): {value} breaches legal limit {limit}")]
+    LegalLimit { channel: u32, param: String, value: f64, limit: f64 },
+    /// Past the corridor edge (`r_x > 1`), or not finite.
+    #[error("channel {channel} ({param}): {value} outside corridor edge {limit}")]
+    CorridorEdge { channel: u32, param: String, value: f64, limit: f64 },
+    /// `r_max - r_min` is zero or not finite, so no reading can be normalized.
+    #[error("channel {channel} ({param}): degenerate corridor [{r_min}, {r_max}]")]
+    DegenerateBand { channel: u32, param: String, r_min: f64, r_max: f64 },
+}
+
+impl CorridorViolation {
+    pub fn channel(&self) -> u32 {
+        match self {
+            CorridorViolation::LegalLimit { channel, .. }
+            | CorridorViolation::CorridorEdge { channel, .. }
+            | CorridorViolation::DegenerateBand { channel, .. } => *channel,
+        }
+    }
+}
+
+/// Graded corridor outcome for a snapshot (K_E_R_Grammar.md E.3).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CorridorDecision {
+    /// Every channel is inside its gold (soft) limit.
+    Ok,
+    /// One or more channels are in the soft band between gold and legal limits.
+    /// Controls should be cut to `factor` in [0,1]; `channels` lists the culprits.
+    /// A reading exactly at its hard limit derates to 0, which cuts every control
+    /// to zero without a breach to report.
+    Derate { factor: f64, channels: Vec<u32> },
+    /// One or more channels crossed their hard band (legal limit or corridor
+    /// edge); stop.
+    Stop { violations: Vec<CorridorViolation> },
+}
+
+impl CorridorDecision {
+    pub fn is_ok(&self) -> bool {
+        matches!(self, CorridorDecision::Ok)
+    }
+}
+
+/// `x` expressed so that larger is always worse for the band's direction.
+fn badness(band: &CorridorBand, x: f64) -> f64 {
+    match band.direction {
+        Direction::Max => x,
+        Direction::Min => -x,
+    }
+}
+
+/// The hard-band breach of `ch`, if any, checked in the order of
+/// `contracts_core::corridor::check_corridors`.
+fn hard_band_violation(ch: &ShardChannel) -> Option<CorridorViolation> {
+    let band = &ch.band;
+    let (channel, param, value) = (band.channel, band.param_name.clone(), ch.value);
+    if !(band.r_max - band.r_min).is_normal() {
+        return Some(CorridorViolation::DegenerateBand { channel, param, r_min: band.r_min, r_max: band.r_max });
+    }
+    if !band.within_corridor(value) {
+        let limit = match band.direction {
+            Direction::Max => band.r_max,
+            Direction::Min => band.r_min,
+        };
+        return Some(CorridorViolation::CorridorEdge { channel, param, value, limit });
+    }
+    match band.legal_limit {
+        Some(limit) if !band.legal_ok(value) => Some(CorridorViolation::LegalLimit { channel, param, value, limit }),
+        _ => None,
+    }
+}
+
+/// Derate factor for a reading in the soft band, or `None` if it is not in it.
+///
+/// Falls linearly from 1 at the gold limit to 0 at the hard limit, which is the
+/// legal limit when set and the corridor edge (`r_x = 1`) otherwise. Bands
+/// without a gold limit have no soft band.
+fn soft_band_factor(ch: &ShardChannel) -> Option<f64> {
+    let band = &ch.band;
+    if band.gold_ok(ch.value) {
+        return None;
+    }
+    let gold = badness(band, band.gold_limit?);
+    let edge = match band.direction {
+        Direction::Max => band.r_max,
+        Direction::Min => band.r_min,
+    };
+    let hard = badness(band, band.legal_limit.unwrap_or(edge));
+    let span = hard - gold;
+    if span <= 0.0 {
+        return Some(0.0);
+    }
+    Some(((hard - badness(band, ch.value)) / span).clamp(0.0, 1.0))
 }
 
 impl CorridorCheck {
+    /// Fails with the first channel past its hard band.
     pub fn check_snapshot(&self, snap: &InfraNodeShardSnapshot) -> Result<(), CorridorViolation> {
-        match contracts_core::corridor::check_corridors(snap) {
-            CorridorResult::Ok => Ok(()),
-            CorridorResult::Violation(msg) => Err(CorridorViolation::Local(msg)),
+        match snap.channels.iter().find_map(hard_band_violation) {
+            Some(v) => Err(v),
+            None => Ok(()),
+        }
+    }
+
+    /// Grade every channel: past the hard band stops, inside the soft band derates
+    /// by the smallest factor among the derating channels.
+    pub fn decide(&self, snap: &InfraNodeShardSnapshot) -> CorridorDecision {
+        let violations: Vec<CorridorViolation> = snap.channels.iter().filter_map(hard_band_violation).collect();
+        if !violations.is_empty() {
+            return CorridorDecision::Stop { violations };
+        }
+        let soft: Vec<(u32, f64)> = snap
+            .channels
+            .iter()
+            .filter_map(|ch| soft_band_factor(ch).map(|f| (ch.band.channel, f)))
+            .collect();
+        if soft.is_empty() {
+            return CorridorDecision::Ok;
         }
+        CorridorDecision::Derate {
+            factor: soft.iter().map(|(_, f)| *f).fold(1.0, f64::min),
+            channels: soft.into_iter().map(|(c, _)| c).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snap(values: [f64; 2]) -> InfraNodeShardSnapshot {
+        let band = |param: &str, direction, channel, gold, legal| CorridorBand {
+            param_name: param.into(),
+            unit: "-".into(),
+            direction,
+            r_min: 0.0,
+            r_max: 100.0,
+            weight_w: 0.5,
+            channel,
+            legal_limit: legal,
+            gold_limit: gold,
+        };
+        InfraNodeShardSnapshot {
+            node_id: "PHX-CYBO-1".into(),
+            timestamp: "2026-01-17T19:27:00Z".into(),
+            channels: vec![
+                ShardChannel { band: band("NOx_stack", Direction::Max, 0, Some(40.0), Some(80.0)), value: values[0] },
+                ShardChannel { band: band("residence", Direction::Min, 1, Some(60.0), None), value: values[1] },
+            ],
+            controls: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_graded_decisions() {
+        let check = CorridorCheck;
+        assert_eq!(check.decide(&snap([30.0, 90.0])), CorridorDecision::Ok);
+
+        // NOx halfway between gold (40) and legal (80); residence 45 is a quarter
+        // of the way from gold (60) to the corridor edge (0).
+        assert_eq!(
+            check.decide(&snap([60.0, 90.0])),
+            CorridorDecision::Derate { factor: 0.5, channels: vec![0] }
+        );
+        assert_eq!(
+            check.decide(&snap([60.0, 45.0])),
+            CorridorDecision::Derate { factor: 0.5, channels: vec![0, 1] }
+        );
+        assert_eq!(
+            check.decide(&snap([30.0, 15.0])),
+            CorridorDecision::Derate { factor: 0.25, channels: vec![1] }
+        );
+        assert!(check.check_snapshot(&snap([60.0, 45.0])).is_ok());
+
+        let legal = CorridorViolation::LegalLimit { channel: 0, param: "NOx_stack".into(), value: 85.0, limit: 80.0 };
+        assert_eq!(check.decide(&snap([85.0, 45.0])), CorridorDecision::Stop { violations: vec![legal.clone()] });
+        assert_eq!(check.check_snapshot(&snap([85.0, 45.0])), Err(legal));
+
+        // Residence below the corridor floor is an edge breach, not a legal one.
+        // At the legal limit the reading is not a breach yet, but leaves nothing to derate to.
+        assert_eq!(check.decide(&snap([80.0, 90.0])), CorridorDecision::Derate { factor: 0.0, channels: vec![0] });
+
+        let stop = check.decide(&snap([85.0, -5.0]));
+        assert!(matches!(&stop, CorridorDecision::Stop { violations } if violations.len() == 2
+            && matches!(violations[1], CorridorViolation::CorridorEdge { channel: 1, value, limit, .. } if value == -5.0 && limit == 0.0)));
+    }
+
+    #[test]
+    fn test_gold_equal_to_legal_has_no_soft_band() {
+        let check = CorridorCheck;
+        let at = |value: f64| {
+            let mut s = snap([value, 90.0]);
+            s.channels[0].band.gold_limit = Some(80.0);
+            s
+        };
+        assert_eq!(check.decide(&at(80.0)), CorridorDecision::Ok);
+        assert!(matches!(check.decide(&at(80.5)), CorridorDecision::Stop { violations } if violations.len() == 1));
     }
 }
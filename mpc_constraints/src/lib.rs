@@ -2,6 +2,6 @@ pub mod corridor;
 pub mod lyapunov;
 pub mod bioscale_guard;
 
-pub use corridor::{CorridorCheck, CorridorViolation};
+pub use corridor::{CorridorCheck, CorridorDecision, CorridorViolation};
 pub use lyapunov::{LyapunovResidualChecker, LyapunovViolation};
 pub use bioscale_guard::{BiocompatGuard, BiocompatViolation};
@@ -0,0 +1,205 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::ker_model::{KerModel, KerPrediction};
+use crate::state::{MpcStateSlice, MpcControlSlice};
+
+/// Plant model used to predict `x` over the horizon.
+pub trait DynamicsModel {
+    fn step(&self, x: &MpcStateSlice, u: &MpcControlSlice, dt: f64) -> MpcStateSlice;
+
+    /// Whether `step` predicts how controls move the state. Checks on the
+    /// predicted trajectory are skipped for models that do not.
+    fn predicts_motion(&self) -> bool {
+        true
+    }
+}
+
+/// Zero-order model: the state does not move within the horizon.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HoldDynamics;
+
+impl DynamicsModel for HoldDynamics {
+    fn step(&self, x: &MpcStateSlice, _u: &MpcControlSlice, _dt: f64) -> MpcStateSlice {
+        x.clone()
+    }
+
+    fn predicts_motion(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DynamicsError {
+    #[error("io error on {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("malformed model file {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+    #[error("matrix shape mismatch: {0}")]
+    Shape(String),
+    #[error("non-finite matrix entry in {0}")]
+    NonFinite(&'static str),
+}
+
+/// Continuous-time linear model `dx/dt = A x + B u + c`, stepped with forward Euler.
+///
+/// States are normalized, so each step clamps `x` back into [0,1]. Loaded from a
+/// TOML file with `a` (n×n), `b` (n×m) and optional `c` (n):
+///
+/// ```toml
+/// a = [[-0.01, 0.0], [0.0, -0.02]]
+/// b = [[-0.005], [0.01]]
+/// c = [0.002, 0.0]
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LinearStateSpace {
+    pub a: Vec<Vec<f64>>,
+    pub b: Vec<Vec<f64>>,
+    #[serde(default)]
+    pub c: Vec<f64>,
+}
+
+impl LinearStateSpace {
+    pub fn new(a: Vec<Vec<f64>>, b: Vec<Vec<f64>>, c: Vec<f64>) -> Result<Self, DynamicsError> {
+        let model = Self { a, b, c };
+        model.validate()?;
+        Ok(model)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, DynamicsError> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path).map_err(|source| DynamicsError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let model: LinearStateSpace = toml::from_str(&data).map_err(|source| DynamicsError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        model.validate()?;
+        Ok(model)
+    }
+
+    pub fn state_dim(&self) -> usize {
+        self.a.len()
+    }
+
+    pub fn control_dim(&self) -> usize {
+        self.b.first().map_or(0, Vec::len)
+    }
+
+    fn validate(&self) -> Result<(), DynamicsError> {
+        let (n, m) = (self.state_dim(), self.control_dim());
+        if let Some(row) = self.a.iter().find(|r| r.len() != n) {
+            return Err(DynamicsError::Shape(format!("A is {n} rows but a row has {} columns", row.len())));
+        }
+        if self.b.len() != n || self.b.iter().any(|r| r.len() != m) {
+            return Err(DynamicsError::Shape(format!("B must be {n}x{m}")));
+        }
+        if !self.c.is_empty() && self.c.len() != n {
+            return Err(DynamicsError::Shape(format!("c has {} entries, expected {n}", self.c.len())));
+        }
+        for (name, rows) in [("A", &self.a), ("B", &self.b)] {
+            if rows.iter().flatten().any(|v| !v.is_finite()) {
+                return Err(DynamicsError::NonFinite(name));
+            }
+        }
+        if self.c.iter().any(|v| !v.is_finite()) {
+            return Err(DynamicsError::NonFinite("c"));
+        }
+        Ok(())
+    }
+}
+
+impl DynamicsModel for LinearStateSpace {
+    /// Missing state or control entries count as 0; extra ones are ignored.
+    fn step(&self, x: &MpcStateSlice, u: &MpcControlSlice, dt: f64) -> MpcStateSlice {
+        let get = |v: &[f64], i: usize| v.get(i).copied().unwrap_or(0.0);
+        let next = (0..self.state_dim())
+            .map(|i| {
+                let ax: f64 = self.a[i].iter().enumerate().map(|(j, a)| a * get(&x.x, j)).sum();
+                let bu: f64 = self.b[i].iter().enumerate().map(|(j, b)| b * get(&u.u, j)).sum();
+                (get(&x.x, i) + dt * (ax + bu + get(&self.c, i))).clamp(0.0, 1.0)
+            })
+            .collect();
+        MpcStateSlice { node_id: x.node_id.clone(), x: next }
+    }
+}
+
+/// Predicted states, controls and KER metrics over one horizon.
+///
+/// `states` has one more entry than `controls`: `states[k + 1]` results from
+/// applying `controls[k]` in `states[k]`, scored by `predictions[k]`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Trajectory {
+    pub states: Vec<MpcStateSlice>,
+    pub controls: Vec<MpcControlSlice>,
+    pub predictions: Vec<KerPrediction>,
+}
+
+/// Roll `controls` forward from `x0` through `dynamics`, predicting KER at each stage.
+pub fn rollout(
+    dynamics: &impl DynamicsModel,
+    ker_model: &impl KerModel,
+    x0: &MpcStateSlice,
+    controls: Vec<MpcControlSlice>,
+    dt: f64,
+) -> Trajectory {
+    let mut states = Vec::with_capacity(controls.len() + 1);
+    let mut predictions = Vec::with_capacity(controls.len());
+    states.push(x0.clone());
+    for u in &controls {
+        let x = states.last().expect("seeded with x0");
+        predictions.push(ker_model.predict(x, u));
+        states.push(dynamics.step(x, u, dt));
+    }
+    Trajectory { states, controls, predictions }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linear_step_and_repo_model() {
+        let model = LinearStateSpace::new(vec![vec![-0.5]], vec![vec![0.2, -0.1]], vec![0.05]).unwrap();
+        let x = MpcStateSlice { node_id: "n".into(), x: vec![0.4] };
+        let u = MpcControlSlice { node_id: "n".into(), u: vec![1.0, 0.5] };
+        // dx/dt = -0.5 * 0.4 + (0.2 * 1.0 - 0.1 * 0.5) + 0.05 = 0: an equilibrium.
+        assert!((model.step(&x, &u, 0.1).x[0] - 0.4).abs() < 1e-12);
+        let idle = MpcControlSlice { node_id: "n".into(), u: vec![0.0, 0.0] };
+        assert!((model.step(&x, &idle, 0.1).x[0] - 0.385).abs() < 1e-12);
+        assert_eq!(model.step(&x, &idle, 100.0).x[0], 0.0);
+
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../models/mpc/phoenix_mar_pump.toml");
+        let repo = LinearStateSpace::load(path).unwrap();
+        assert_eq!((repo.state_dim(), repo.control_dim()), (2, 1));
+
+        assert!(matches!(
+            LinearStateSpace::new(vec![vec![1.0, 0.0]], vec![vec![1.0]], vec![]),
+            Err(DynamicsError::Shape(_))
+        ));
+        assert!(matches!(
+            LinearStateSpace::new(vec![vec![f64::NAN]], vec![vec![1.0]], vec![]),
+            Err(DynamicsError::NonFinite("A"))
+        ));
+    }
+
+    #[test]
+    fn test_rollout_shapes() {
+        let model = LinearStateSpace::new(vec![vec![0.0]], vec![vec![0.1]], vec![]).unwrap();
+        let x0 = MpcStateSlice { node_id: "n".into(), x: vec![0.0] };
+        let controls = vec![MpcControlSlice { node_id: "n".into(), u: vec![1.0] }; 3];
+        let traj = rollout(&model, &crate::ker_model::AffineKerModel::default(), &x0, controls, 1.0);
+        assert_eq!((traj.states.len(), traj.predictions.len()), (4, 3));
+        assert!((traj.states[3].x[0] - 0.3).abs() < 1e-12);
+    }
+}
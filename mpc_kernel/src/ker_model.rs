@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+use contracts_core::metrics::{KerVector, RiskScalar, EcoImpactScalar};
+use crate::state::{MpcStateSlice, MpcControlSlice};
+
+/// Predicted KER metrics, eco-impact and risk for applying `u` in state `x`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct KerPrediction {
+    pub ker: KerVector,
+    pub eco: EcoImpactScalar,
+    pub risk: RiskScalar,
+}
+
+/// Maps a candidate control onto the quantities `BiocompatObjective::eval` scores.
+pub trait KerModel {
+    fn predict(&self, x: &MpcStateSlice, u: &MpcControlSlice) -> KerPrediction;
+}
+
+/// KER metrics affine in `x` and `u`: `base + Σ_i state_gain[i] * x_i + Σ_i gain[i] * u_i`,
+/// per field.
+///
+/// Fractions and indices are clamped to [0,1], `exergy_cost` to >= 0. States and
+/// controls beyond the gain vectors have no effect.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AffineKerModel {
+    pub base: KerPrediction,
+    pub gain: Vec<KerPrediction>,
+    #[serde(default)]
+    pub state_gain: Vec<KerPrediction>,
+}
+
+fn accumulate(p: &mut KerPrediction, gain: &[KerPrediction], v: &[f64]) {
+    for (g, &vi) in gain.iter().zip(v) {
+        p.ker.exergy_cost += g.ker.exergy_cost * vi;
+        p.ker.degradation_index += g.ker.degradation_index * vi;
+        p.ker.active_node_fraction += g.ker.active_node_fraction * vi;
+        p.ker.sla_violation_ratio += g.ker.sla_violation_ratio * vi;
+        p.eco += g.eco * vi;
+        p.risk += g.risk * vi;
+    }
+}
+
+impl KerModel for AffineKerModel {
+    fn predict(&self, x: &MpcStateSlice, u: &MpcControlSlice) -> KerPrediction {
+        let mut p = self.base.clone();
+        accumulate(&mut p, &self.state_gain, &x.x);
+        accumulate(&mut p, &self.gain, &u.u);
+        p.ker.exergy_cost = p.ker.exergy_cost.max(0.0);
+        p.ker.degradation_index = p.ker.degradation_index.clamp(0.0, 1.0);
+        p.ker.active_node_fraction = p.ker.active_node_fraction.clamp(0.0, 1.0);
+        p.ker.sla_violation_ratio = p.ker.sla_violation_ratio.clamp(0.0, 1.0);
+        p.eco = p.eco.clamp(0.0, 1.0);
+        p.risk = p.risk.clamp(0.0, 1.0);
+        p
+    }
+}
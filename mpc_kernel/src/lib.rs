@@ -1,11 +1,19 @@
 pub mod state;
 pub mod objective;
 pub mod solver;
+pub mod ker_model;
+pub mod dynamics;
+pub mod projected_gradient;
 
-pub use state::{MpcStateSlice, MpcControlSlice};
+pub use state::{MpcStateSlice, MpcControlSlice, StateExtractor};
 pub use objective::{
     BiocompatObjectiveConfig,
     BiocompatObjective,
     ObjectiveTermWeights,
+    RewardTerm,
+    BiocompatError,
 };
-pub use solver::{MpcSolver, MpcSolveError};
+pub use solver::{MpcHorizonConfig, MpcSolution, MpcSolver, MpcSolveError};
+pub use dynamics::{DynamicsModel, DynamicsError, HoldDynamics, LinearStateSpace, Trajectory, rollout};
+pub use ker_model::{KerModel, KerPrediction, AffineKerModel};
+pub use projected_gradient::{ProjectedGradientSolver, ProjectedGradientConfig};
@@ -1,14 +1,42 @@
 use serde::{Deserialize, Serialize};
 use contracts_core::metrics::{KerVector, RiskScalar, EcoImpactScalar};
+use crate::dynamics::Trajectory;
 use crate::state::{MpcStateSlice, MpcControlSlice};
 
 /// Dimensionless weight vector for objective terms, all >= 0.[file:39]
-#[derive(Clone, Debug, Serialize, Deserialize)]
+///
+/// The state, terminal, effort and move weights default to 0 so configs written
+/// before they existed keep their meaning.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct ObjectiveTermWeights {
     pub lambda_energy: f64,      // kWh or exergy per unit work.[file:39]
     pub lambda_degradation: f64, // hardware / tissue degradation proxy.[file:91]
     pub lambda_sparsity: f64,    // active node count.[file:39]
     pub lambda_slaviolation: f64,
+    /// Stage cost on ||x||², x being normalized risk coordinates.
+    #[serde(default)]
+    pub lambda_state: f64,
+    /// Terminal cost on ||x_H||² for the final predicted state.
+    #[serde(default)]
+    pub lambda_terminal: f64,
+    /// Control effort ||u||² per stage.
+    #[serde(default)]
+    pub lambda_effort: f64,
+    /// Control move ||u_k - u_{k-1}||²; damps chattering between steps.
+    #[serde(default)]
+    pub lambda_delta_u: f64,
+}
+
+fn sq_norm(v: &[f64]) -> f64 {
+    v.iter().map(|x| x * x).sum()
+}
+
+/// ||a - b||², with entries missing from the shorter side counted as 0.
+fn sq_dist(a: &[f64], b: &[f64]) -> f64 {
+    (0..a.len().max(b.len()))
+        .map(|i| a.get(i).copied().unwrap_or(0.0) - b.get(i).copied().unwrap_or(0.0))
+        .map(|d| d * d)
+        .sum()
 }
 
 /// Biocompatibility upgrade envelope, enforcing bioscale safety and neurorights.[file:91][file:88]
@@ -22,13 +50,38 @@ pub struct BiocompatObjectiveConfig {
     pub forbid_distress_coupling: bool,
     /// Optional cap on cognitive load index for human-integrated planes.[file:85]
     pub max_cognitive_load: Option<f64>,
+    /// Position of the cognitive-load index (0–1) in the state slice `x`; required
+    /// when `max_cognitive_load` is set.
+    #[serde(default)]
+    pub cognitive_load_index: Option<usize>,
     pub weights: ObjectiveTermWeights,
+    /// Reward terms credited against the cost, e.g. throughput per unit of control.
+    #[serde(default)]
+    pub rewards: Vec<RewardTerm>,
+}
+
+/// Linear reward `weight * u[control_index]` subtracted from the stage cost.
+///
+/// The control is clamped to [0,1], so a stage earns at most `weight`. J may go
+/// negative, but a reward never offsets an envelope breach: those stages cost
+/// infinity before any reward is credited.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RewardTerm {
+    pub name: String,
+    pub control_index: usize,
+    pub weight: f64,
+    /// The reward tracks a biological distress or coercion signal; rejected by
+    /// `new_checked` under `forbid_distress_coupling`.
+    #[serde(default)]
+    pub distress_coupled: bool,
 }
 
 /// Runtime objective that is guaranteed to be bioscale-compatible if constructed via `new_checked`.[file:91]
 #[derive(Clone, Debug)]
 pub struct BiocompatObjective {
     cfg: BiocompatObjectiveConfig,
+    /// Control currently applied to the plant; the first Δu term is measured from it.
+    u_prev: Option<MpcControlSlice>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -39,6 +92,12 @@ pub enum BiocompatError {
     InvalidRisk(RiskScalar),
     #[error("non-finite weight encountered")]
     NonFiniteWeight,
+    #[error("invalid cognitive load cap: {0}")]
+    InvalidCognitiveLoad(f64),
+    #[error("max_cognitive_load is set but cognitive_load_index is not")]
+    MissingCognitiveLoadIndex,
+    #[error("reward term '{0}' couples to distress signals")]
+    DistressCoupledReward(String),
 }
 
 impl BiocompatObjective {
@@ -54,15 +113,58 @@ impl BiocompatObjective {
             cfg.weights.lambda_degradation,
             cfg.weights.lambda_sparsity,
             cfg.weights.lambda_slaviolation,
+            cfg.weights.lambda_state,
+            cfg.weights.lambda_terminal,
+            cfg.weights.lambda_effort,
+            cfg.weights.lambda_delta_u,
         ] {
             if !w.is_finite() || w < 0.0 {
                 return Err(BiocompatError::NonFiniteWeight);
             }
         }
-        Ok(Self { cfg })
+        if let Some(cap) = cfg.max_cognitive_load {
+            if !(0.0..=1.0).contains(&cap) {
+                return Err(BiocompatError::InvalidCognitiveLoad(cap));
+            }
+            if cfg.cognitive_load_index.is_none() {
+                return Err(BiocompatError::MissingCognitiveLoadIndex);
+            }
+        }
+        for r in &cfg.rewards {
+            if !r.weight.is_finite() || r.weight < 0.0 {
+                return Err(BiocompatError::NonFiniteWeight);
+            }
+            if cfg.forbid_distress_coupling && r.distress_coupled {
+                return Err(BiocompatError::DistressCoupledReward(r.name.clone()));
+            }
+        }
+        Ok(Self { cfg, u_prev: None })
+    }
+
+    /// Same objective, with `u` as the control in force before the horizon starts.
+    pub fn with_previous_control(&self, u: MpcControlSlice) -> Self {
+        Self { cfg: self.cfg.clone(), u_prev: Some(u) }
+    }
+
+    pub fn config(&self) -> &BiocompatObjectiveConfig {
+        &self.cfg
+    }
+
+    /// Cognitive-load index read from `x`, if the objective is configured to track one.
+    pub fn cognitive_load(&self, x: &MpcStateSlice) -> Option<f64> {
+        self.cfg.cognitive_load_index.and_then(|i| x.x.get(i).copied())
     }
 
-    /// Evaluate J(x,u) under the biocompatibility envelope.[file:39][file:91]
+    /// The state exceeds the cognitive-load cap. A missing or non-finite reading
+    /// counts as over the cap.
+    pub fn cognitive_load_exceeded(&self, x: &MpcStateSlice) -> bool {
+        match self.cfg.max_cognitive_load {
+            Some(cap) => self.cognitive_load(x).is_none_or(|load| !load.is_finite() || load > cap),
+            None => false,
+        }
+    }
+
+    /// Evaluate the stage cost J(x,u) under the biocompatibility envelope.[file:39][file:91]
     pub fn eval(
         &self,
         x: &MpcStateSlice,
@@ -75,14 +177,194 @@ impl BiocompatObjective {
         if eco < self.cfg.e_min || risk > self.cfg.r_max {
             return f64::INFINITY;
         }
+        // Cognitive-load cap is a hard constraint, like the risk ceiling.[file:85]
+        if self.cognitive_load_exceeded(x) {
+            return f64::INFINITY;
+        }
 
         // Example placeholder terms: energy, degradation, sparsity, SLA.[file:39]
         let energy_term = self.cfg.weights.lambda_energy * ker.exergy_cost;
         let degr_term = self.cfg.weights.lambda_degradation * ker.degradation_index;
         let sparsity_term = self.cfg.weights.lambda_sparsity * ker.active_node_fraction;
         let sla_term = self.cfg.weights.lambda_slaviolation * ker.sla_violation_ratio;
+        let state_term = self.cfg.weights.lambda_state * sq_norm(&x.x);
+        let effort_term = self.cfg.weights.lambda_effort * sq_norm(&u.u);
+        // Distress-coupled rewards never reach here when forbidden; new_checked rejects them.
+        let reward: f64 = self
+            .cfg
+            .rewards
+            .iter()
+            .map(|r| r.weight * u.u.get(r.control_index).map_or(0.0, |v| v.clamp(0.0, 1.0)))
+            .sum();
+
+        // Lower J is better but cannot trade off below eco/risk floors.[file:39]
+        energy_term + degr_term + sparsity_term + sla_term + state_term + effort_term - reward
+    }
+
+    /// Terminal cost on the state the horizon ends in.
+    pub fn terminal_cost(&self, x: &MpcStateSlice) -> f64 {
+        self.cfg.weights.lambda_terminal * sq_norm(&x.x)
+    }
+
+    /// Δu penalty over a control sequence, starting from the previous control if set.
+    pub fn move_cost(&self, controls: &[MpcControlSlice]) -> f64 {
+        let w = self.cfg.weights.lambda_delta_u;
+        if w == 0.0 {
+            return 0.0;
+        }
+        let first = match (&self.u_prev, controls.first()) {
+            (Some(prev), Some(u0)) => sq_dist(&u0.u, &prev.u),
+            _ => 0.0,
+        };
+        let rest: f64 = controls.windows(2).map(|p| sq_dist(&p[1].u, &p[0].u)).sum();
+        w * (first + rest)
+    }
+
+    /// Stage costs over the horizon plus terminal and move costs; infinite if any
+    /// stage leaves the eco/risk envelope or any state, the terminal one
+    /// included, is over the cognitive-load cap.
+    pub fn eval_trajectory(&self, traj: &Trajectory) -> f64 {
+        // The terminal state has no stage of its own, so check its cap here.
+        if traj.states.last().is_some_and(|x| self.cognitive_load_exceeded(x)) {
+            return f64::INFINITY;
+        }
+        let stages: f64 = traj
+            .states
+            .iter()
+            .zip(&traj.controls)
+            .zip(&traj.predictions)
+            .map(|((x, u), p)| self.eval(x, u, &p.ker, p.eco, p.risk))
+            .sum();
+        let terminal = traj.states.last().map_or(0.0, |x| self.terminal_cost(x));
+        stages + terminal + self.move_cost(&traj.controls)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ker_model::KerPrediction;
+
+    fn slice_x(x: Vec<f64>) -> MpcStateSlice {
+        MpcStateSlice { node_id: "n".into(), x }
+    }
+
+    fn slice_u(u: Vec<f64>) -> MpcControlSlice {
+        MpcControlSlice { node_id: "n".into(), u }
+    }
+
+    fn objective(weights: ObjectiveTermWeights) -> BiocompatObjective {
+        BiocompatObjective::new_checked(BiocompatObjectiveConfig {
+            e_min: 0.2,
+            r_max: 0.8,
+            forbid_distress_coupling: true,
+            max_cognitive_load: None,
+            cognitive_load_index: None,
+            weights,
+            rewards: Vec::new(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_stage_terminal_and_move_costs() {
+        let obj = objective(ObjectiveTermWeights {
+            lambda_state: 1.0,
+            lambda_terminal: 10.0,
+            lambda_effort: 0.5,
+            lambda_delta_u: 2.0,
+            ..Default::default()
+        });
+        let p = KerPrediction { eco: 0.5, risk: 0.1, ..Default::default() };
+        let traj = Trajectory {
+            states: vec![slice_x(vec![0.3, 0.4]), slice_x(vec![0.2, 0.0]), slice_x(vec![0.1, 0.0])],
+            controls: vec![slice_u(vec![1.0]), slice_u(vec![0.5])],
+            predictions: vec![p.clone(), p],
+        };
+        // Stages: (0.25 + 0.5) + (0.04 + 0.125); terminal 10 * 0.01; moves 2 * 0.25.
+        let expected = 0.75 + 0.165 + 0.1 + 0.5;
+        assert!((obj.eval_trajectory(&traj) - expected).abs() < 1e-12);
+
+        // Measured from the applied control, the first move adds 2 * 0.36.
+        let held = obj.with_previous_control(slice_u(vec![0.4]));
+        assert!((held.eval_trajectory(&traj) - (expected + 0.72)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_weights_default_from_older_configs() {
+        let w: ObjectiveTermWeights = toml::from_str(
+            "lambda_energy = 1.0\nlambda_degradation = 0.0\nlambda_sparsity = 0.0\nlambda_slaviolation = 0.0\n",
+        )
+        .unwrap();
+        assert_eq!(w.lambda_delta_u, 0.0);
+        let bad = ObjectiveTermWeights { lambda_delta_u: -1.0, ..Default::default() };
+        assert!(matches!(
+            BiocompatObjective::new_checked(BiocompatObjectiveConfig {
+                e_min: 0.2,
+                r_max: 0.8,
+                forbid_distress_coupling: true,
+                max_cognitive_load: None,
+                cognitive_load_index: None,
+                weights: bad,
+                rewards: Vec::new(),
+            }),
+            Err(BiocompatError::NonFiniteWeight)
+        ));
+    }
+
+    #[test]
+    fn test_cognitive_load_cap_and_distress_rewards() {
+        let cfg = BiocompatObjectiveConfig {
+            e_min: 0.2,
+            r_max: 0.8,
+            forbid_distress_coupling: true,
+            max_cognitive_load: Some(0.6),
+            cognitive_load_index: Some(1),
+            weights: ObjectiveTermWeights::default(),
+            rewards: vec![RewardTerm {
+                name: "throughput".into(),
+                control_index: 0,
+                weight: 0.5,
+                distress_coupled: false,
+            }],
+        };
+        let obj = BiocompatObjective::new_checked(cfg.clone()).unwrap();
+        let ker = KerVector::default();
+        let u = slice_u(vec![1.0]);
+        assert_eq!(obj.eval(&slice_x(vec![0.1, 0.5]), &u, &ker, 0.5, 0.1), -0.5);
+        assert_eq!(obj.eval(&slice_x(vec![0.1, 0.7]), &u, &ker, 0.5, 0.1), f64::INFINITY);
+        // No load reading at all fails closed.
+        assert_eq!(obj.eval(&slice_x(vec![0.1]), &u, &ker, 0.5, 0.1), f64::INFINITY);
+
+        // The reward is bounded by its weight and cannot buy back a breach.
+        assert_eq!(obj.eval(&slice_x(vec![0.1, 0.5]), &slice_u(vec![1e9]), &ker, 0.5, 0.1), -0.5);
+        assert_eq!(obj.eval(&slice_x(vec![0.1, 0.5]), &slice_u(vec![1e9]), &ker, 0.5, 0.9), f64::INFINITY);
+
+        // A terminal state over the cap makes the whole trajectory infeasible.
+        let p = crate::ker_model::KerPrediction { eco: 0.5, risk: 0.1, ..Default::default() };
+        let mut traj = Trajectory {
+            states: vec![slice_x(vec![0.1, 0.5]), slice_x(vec![0.1, 0.5])],
+            controls: vec![u.clone()],
+            predictions: vec![p],
+        };
+        assert_eq!(obj.eval_trajectory(&traj), -0.5);
+        traj.states[1] = slice_x(vec![0.1, 0.7]);
+        assert_eq!(obj.eval_trajectory(&traj), f64::INFINITY);
+
+        let mut coupled = cfg.clone();
+        coupled.rewards[0].distress_coupled = true;
+        assert!(matches!(
+            BiocompatObjective::new_checked(coupled.clone()),
+            Err(BiocompatError::DistressCoupledReward(name)) if name == "throughput"
+        ));
+        coupled.forbid_distress_coupling = false;
+        assert!(BiocompatObjective::new_checked(coupled).is_ok());
 
-        // All terms are non-negative; lower J is better but cannot trade off below eco/risk floors.[file:39]
-        energy_term + degr_term + sparsity_term + sla_term
+        let mut unindexed = cfg;
+        unindexed.cognitive_load_index = None;
+        assert!(matches!(
+            BiocompatObjective::new_checked(unindexed),
+            Err(BiocompatError::MissingCognitiveLoadIndex)
+        ));
     }
 }
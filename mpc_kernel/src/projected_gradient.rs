@@ -0,0 +1,378 @@
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+use contracts_core::infra::InfraNodeShardId;
+use crate::dynamics::{rollout, DynamicsModel, HoldDynamics};
+use crate::ker_model::KerModel;
+use crate::objective::BiocompatObjective;
+use crate::solver::{MpcHorizonConfig, MpcSolution, MpcSolveError, MpcSolver};
+use crate::state::{MpcStateSlice, MpcControlSlice};
+
+/// Tuning for [`ProjectedGradientSolver`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProjectedGradientConfig {
+    /// Number of controls m per step.
+    pub control_dim: usize,
+    /// Starting value for every control, in [0,1].
+    pub initial_u: f64,
+    /// First step length tried by the backtracking line search.
+    pub step_size: f64,
+    /// Finite-difference step for the numerical gradient.
+    pub fd_step: f64,
+    /// Stop once an iteration improves the cost by less than this.
+    pub tolerance: f64,
+}
+
+impl Default for ProjectedGradientConfig {
+    fn default() -> Self {
+        Self { control_dim: 1, initial_u: 0.5, step_size: 0.5, fd_step: 1e-4, tolerance: 1e-9 }
+    }
+}
+
+/// Maximum step halvings per line search.
+const MAX_BACKTRACKS: usize = 40;
+/// Armijo sufficient-decrease constant.
+const ARMIJO_C: f64 = 1e-4;
+/// Halvings of the constant-seed grid tried when no coarse seed is feasible;
+/// the finest grid spacing is `2^-SEED_REFINEMENTS`.
+const SEED_REFINEMENTS: u32 = 8;
+
+/// Box-constrained projected-gradient solver over the whole control sequence.
+///
+/// The decision variable is `u_0..u_{H-1}`, each in [0,1]^m. Each candidate is
+/// rolled out through the `DynamicsModel` and scored with
+/// `BiocompatObjective::eval_trajectory` on the predicted states. The gradient
+/// is taken by finite differences, so the solver is deterministic and needs no
+/// external QP library. Infinite cost (outside the eco/risk envelope) is
+/// treated as infeasible: line-search steps into it are rejected.
+///
+/// When none of the coarse seeds is feasible, constant seeds on a grid halved
+/// up to `SEED_REFINEMENTS` times are tried, so a feasible band narrower than
+/// the gaps between coarse seeds is still found.
+///
+/// The deadline is checked before each refinement round of the seed search,
+/// each iteration, each gradient coordinate and each line-search trial. A
+/// solve overruns it by at most one refinement round (up to 128 rollouts), or
+/// by the 4 coarse seeds, and otherwise by two rollouts. Past the deadline the
+/// best feasible iterate is returned; with no feasible seed yet the solve fails
+/// with `MpcSolveError::Timeout`.
+#[derive(Clone, Debug)]
+pub struct ProjectedGradientSolver<M: KerModel, D: DynamicsModel = HoldDynamics> {
+    cfg: ProjectedGradientConfig,
+    model: M,
+    dynamics: D,
+}
+
+impl<M: KerModel> ProjectedGradientSolver<M> {
+    /// Solver that holds the state at `x0` over the horizon.
+    pub fn new(cfg: ProjectedGradientConfig, model: M) -> Self {
+        Self { cfg, model, dynamics: HoldDynamics }
+    }
+}
+
+impl<M: KerModel, D: DynamicsModel> ProjectedGradientSolver<M, D> {
+    pub fn with_dynamics(cfg: ProjectedGradientConfig, model: M, dynamics: D) -> Self {
+        Self { cfg, model, dynamics }
+    }
+
+    fn check_config(&self, horizon: &MpcHorizonConfig) -> Result<(), MpcSolveError> {
+        let c = &self.cfg;
+        let ok = horizon.horizon_steps > 0
+            && horizon.dt_seconds.is_finite()
+            && horizon.dt_seconds > 0.0
+            && c.control_dim > 0
+            && (0.0..=1.0).contains(&c.initial_u)
+            && c.step_size.is_finite()
+            && c.step_size > 0.0
+            && c.fd_step.is_finite()
+            && c.fd_step > 0.0
+            && c.tolerance >= 0.0;
+        if ok { Ok(()) } else { Err(MpcSolveError::InvalidConfig) }
+    }
+
+    /// Cost of the trajectory a flattened control sequence produces; NaN counts as infeasible.
+    fn cost(&self, dt: f64, obj: &BiocompatObjective, x0: &MpcStateSlice, seq: &[f64]) -> f64 {
+        let controls = split(&x0.node_id, seq, self.cfg.control_dim);
+        let total = obj.eval_trajectory(&rollout(&self.dynamics, &self.model, x0, controls, dt));
+        if total.is_nan() { f64::INFINITY } else { total }
+    }
+
+    /// Finite-difference gradient at `seq`, or `None` once `expired` reports
+    /// the deadline passed.
+    fn gradient(
+        &self,
+        dt: f64,
+        obj: &BiocompatObjective,
+        x0: &MpcStateSlice,
+        seq: &[f64],
+        j: f64,
+        expired: &dyn Fn() -> bool,
+    ) -> Option<Vec<f64>> {
+        let h = self.cfg.fd_step;
+        let mut probe = seq.to_vec();
+        (0..seq.len())
+            .map(|i| {
+                if expired() {
+                    return None;
+                }
+                let ui = seq[i];
+                let (up, dn) = ((ui + h).min(1.0), (ui - h).max(0.0));
+                probe[i] = up;
+                let j_up = self.cost(dt, obj, x0, &probe);
+                probe[i] = dn;
+                let j_dn = self.cost(dt, obj, x0, &probe);
+                probe[i] = ui;
+                Some(match (j_up.is_finite() && up > ui, j_dn.is_finite() && dn < ui) {
+                    (true, true) => (j_up - j_dn) / (up - dn),
+                    (true, false) => (j_up - j) / (up - ui),
+                    (false, true) => (j - j_dn) / (ui - dn),
+                    (false, false) => 0.0,
+                })
+            })
+            .collect()
+    }
+}
+
+fn split(node_id: &InfraNodeShardId, seq: &[f64], m: usize) -> Vec<MpcControlSlice> {
+    seq.chunks(m)
+        .map(|u| MpcControlSlice { node_id: node_id.clone(), u: u.to_vec() })
+        .collect()
+}
+
+impl<M: KerModel, D: DynamicsModel> MpcSolver for ProjectedGradientSolver<M, D> {
+    fn solve(
+        &self,
+        cfg: &MpcHorizonConfig,
+        obj: &BiocompatObjective,
+        x0: &MpcStateSlice,
+        deadline: Option<Instant>,
+    ) -> Result<MpcSolution, MpcSolveError> {
+        self.check_config(cfg)?;
+        let expired = || deadline.is_some_and(|d| Instant::now() >= d);
+        let n = cfg.horizon_steps * self.cfg.control_dim;
+
+        // Seed from the configured start and the box centre/corners, then from
+        // ever finer grids; keep the best finite seed of the first round that has one.
+        let best_seed = |values: &mut dyn Iterator<Item = f64>| {
+            values
+                .map(|v| {
+                    let seq = vec![v; n];
+                    let j = self.cost(cfg.dt_seconds, obj, x0, &seq);
+                    (seq, j)
+                })
+                .filter(|(_, j)| j.is_finite())
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+        };
+        let mut seed = best_seed(&mut [self.cfg.initial_u, 0.5, 0.0, 1.0].into_iter());
+        for level in 2..=SEED_REFINEMENTS {
+            if seed.is_some() {
+                break;
+            }
+            if expired() {
+                return Err(MpcSolveError::Timeout);
+            }
+            let cells = 1u32 << level;
+            seed = best_seed(&mut (1..cells).step_by(2).map(|i| f64::from(i) / f64::from(cells)));
+        }
+        let (mut seq, mut j) = seed.ok_or(MpcSolveError::Infeasible)?;
+
+        let mut iterations = 0;
+        let mut timed_out = false;
+        while iterations < cfg.max_iterations {
+            if expired() {
+                timed_out = true;
+                break;
+            }
+            iterations += 1;
+            let Some(grad) = self.gradient(cfg.dt_seconds, obj, x0, &seq, j, &expired) else {
+                timed_out = true;
+                break;
+            };
+            if grad.iter().all(|g| *g == 0.0) {
+                break;
+            }
+            let mut alpha = self.cfg.step_size;
+            let mut accepted = None;
+            for _ in 0..MAX_BACKTRACKS {
+                if expired() {
+                    timed_out = true;
+                    break;
+                }
+                let cand: Vec<f64> = seq.iter().zip(&grad).map(|(u, g)| (u - alpha * g).clamp(0.0, 1.0)).collect();
+                let j_cand = self.cost(cfg.dt_seconds, obj, x0, &cand);
+                let decrease: f64 = grad.iter().zip(seq.iter().zip(&cand)).map(|(g, (u, c))| g * (u - c)).sum();
+                if j_cand.is_finite() && j_cand <= j - ARMIJO_C * decrease && j_cand < j {
+                    accepted = Some((cand, j_cand));
+                    break;
+                }
+                alpha *= 0.5;
+            }
+            let Some((cand, j_cand)) = accepted else { break };
+            let improvement = j - j_cand;
+            seq = cand;
+            j = j_cand;
+            if improvement < self.cfg.tolerance {
+                break;
+            }
+        }
+
+        Ok(MpcSolution { controls: split(&x0.node_id, &seq, self.cfg.control_dim), iterations, timed_out })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use contracts_core::metrics::KerVector;
+    use crate::ker_model::{AffineKerModel, KerPrediction};
+    use crate::objective::{BiocompatObjectiveConfig, ObjectiveTermWeights};
+
+    fn objective(r_max: f64) -> BiocompatObjective {
+        BiocompatObjective::new_checked(BiocompatObjectiveConfig {
+            e_min: 0.2,
+            r_max,
+            forbid_distress_coupling: true,
+            max_cognitive_load: None,
+            cognitive_load_index: None,
+            weights: ObjectiveTermWeights {
+                lambda_energy: 1.0,
+                lambda_degradation: 0.0,
+                lambda_sparsity: 0.0,
+                lambda_slaviolation: 2.0,
+                ..Default::default()
+            },
+            rewards: Vec::new(),
+        })
+        .unwrap()
+    }
+
+    /// One pump: more speed costs exergy but cuts SLA misses more, and raises risk.
+    fn pump_model() -> AffineKerModel {
+        AffineKerModel {
+            base: KerPrediction {
+                ker: KerVector { exergy_cost: 1.0, sla_violation_ratio: 1.0, ..Default::default() },
+                eco: 0.6,
+                risk: 0.2,
+            },
+            gain: vec![KerPrediction {
+                ker: KerVector { exergy_cost: 1.0, sla_violation_ratio: -1.0, ..Default::default() },
+                eco: 0.0,
+                risk: 0.8,
+            }],
+            state_gain: Vec::new(),
+        }
+    }
+
+    fn x0() -> MpcStateSlice {
+        MpcStateSlice { node_id: "PHX-MAR-01".into(), x: vec![0.4] }
+    }
+
+    fn horizon(max_iterations: usize) -> MpcHorizonConfig {
+        MpcHorizonConfig { horizon_steps: 3, dt_seconds: 60.0, max_iterations }
+    }
+
+    #[test]
+    fn test_converges_to_risk_boundary_inside_box() {
+        let solver = ProjectedGradientSolver::new(ProjectedGradientConfig { initial_u: 0.0, ..Default::default() }, pump_model());
+        // Cost falls with u, but risk = 0.2 + 0.8u caps u at 0.4375 for r_max = 0.55.
+        let controls = solver.solve(&horizon(200), &objective(0.55), &x0(), None).unwrap().controls;
+        assert_eq!(controls.len(), 3);
+        for c in &controls {
+            assert_eq!(c.u.len(), 1);
+            assert!(c.u[0] <= 0.4375 && c.u[0] > 0.43, "u = {}", c.u[0]);
+        }
+        assert_eq!(solver.solve(&horizon(200), &objective(0.55), &x0(), None).unwrap().controls[0].u, controls[0].u);
+
+        // With the cap lifted the optimum is the box edge.
+        let controls = solver.solve(&horizon(200), &objective(1.0), &x0(), None).unwrap().controls;
+        assert!(controls.iter().all(|c| c.u == vec![1.0]));
+    }
+
+    #[test]
+    fn test_iteration_budget_and_errors() {
+        let solver = ProjectedGradientSolver::new(ProjectedGradientConfig { initial_u: 0.0, ..Default::default() }, pump_model());
+        let start = solver.solve(&horizon(0), &objective(0.55), &x0(), None).unwrap();
+        assert!(start.controls.iter().all(|c| c.u == vec![0.0]));
+        assert_eq!((start.iterations, start.timed_out), (0, false));
+
+        // A deadline already passed still yields the best feasible seed.
+        let late = solver.solve(&horizon(200), &objective(0.55), &x0(), Some(Instant::now())).unwrap();
+        assert!(late.timed_out && late.iterations == 0);
+        assert!(late.controls.iter().all(|c| c.u == vec![0.0]));
+        let done = solver.solve(&horizon(200), &objective(0.55), &x0(), Some(Instant::now() + std::time::Duration::from_secs(60))).unwrap();
+        assert!(!done.timed_out && done.iterations > 0);
+
+        // Base risk already above the ceiling: no candidate is finite.
+        let mut model = pump_model();
+        model.base.risk = 0.9;
+        let solver = ProjectedGradientSolver::new(ProjectedGradientConfig::default(), model);
+        assert!(matches!(solver.solve(&horizon(10), &objective(0.55), &x0(), None), Err(MpcSolveError::Infeasible)));
+
+        let bad = MpcHorizonConfig { horizon_steps: 0, dt_seconds: 60.0, max_iterations: 10 };
+        assert!(matches!(solver.solve(&bad, &objective(0.55), &x0(), None), Err(MpcSolveError::InvalidConfig)));
+    }
+
+    #[test]
+    fn test_finds_feasible_band_between_coarse_seeds() {
+        // eco = u needs u >= 0.2 and risk = 0.2 + 0.8u needs u <= 0.3125; every
+        // coarse seed (0, 0.5, 1) falls outside that band.
+        let model = AffineKerModel {
+            base: KerPrediction { risk: 0.2, ..Default::default() },
+            gain: vec![KerPrediction {
+                ker: KerVector { exergy_cost: 1.0, ..Default::default() },
+                eco: 1.0,
+                risk: 0.8,
+            }],
+            state_gain: Vec::new(),
+        };
+        let solver = ProjectedGradientSolver::new(ProjectedGradientConfig::default(), model);
+        let plan = solver.solve(&horizon(200), &objective(0.45), &x0(), None).unwrap();
+        assert!(plan.controls.iter().all(|c| (0.2..=0.3125).contains(&c.u[0])), "{:?}", plan.controls);
+
+        // Past the deadline the refinement rounds are not started, and there is no start to return.
+        let late = solver.solve(&horizon(200), &objective(0.45), &x0(), Some(Instant::now()));
+        assert!(matches!(late, Err(MpcSolveError::Timeout)));
+    }
+
+    #[test]
+    fn test_dynamics_shape_the_plan() {
+        // Risk tracks the predicted state; pumping only costs exergy.
+        let model = AffineKerModel {
+            state_gain: vec![KerPrediction { risk: 1.0, ..Default::default() }],
+            base: KerPrediction { eco: 0.6, ..Default::default() },
+            gain: vec![KerPrediction {
+                ker: KerVector { exergy_cost: 0.1, ..Default::default() },
+                ..Default::default()
+            }],
+        };
+        let objective = BiocompatObjective::new_checked(BiocompatObjectiveConfig {
+            e_min: 0.2,
+            r_max: 0.5,
+            forbid_distress_coupling: true,
+            max_cognitive_load: None,
+            cognitive_load_index: None,
+            weights: ObjectiveTermWeights {
+                lambda_energy: 1.0,
+                lambda_degradation: 0.0,
+                lambda_sparsity: 0.0,
+                lambda_slaviolation: 0.0,
+                ..Default::default()
+            },
+            rewards: Vec::new(),
+        })
+        .unwrap();
+        let x0 = MpcStateSlice { node_id: "PHX-MAR-01".into(), x: vec![0.45] };
+        let horizon = MpcHorizonConfig { horizon_steps: 3, dt_seconds: 1.0, max_iterations: 200 };
+
+        // Holding x: risk 0.45 is fine everywhere, so the cheapest plan is idle.
+        let held = ProjectedGradientSolver::new(ProjectedGradientConfig::default(), model.clone());
+        assert!(held.solve(&horizon, &objective, &x0, None).unwrap().controls.iter().all(|c| c.u[0] < 1e-6));
+
+        // Inflow of 0.04/s takes x to 0.53 by the last scored stage unless pumped at 0.1/s per unit u.
+        let dynamics = crate::dynamics::LinearStateSpace::new(vec![vec![0.0]], vec![vec![-0.1]], vec![0.04]).unwrap();
+        let pumped = ProjectedGradientSolver::with_dynamics(ProjectedGradientConfig::default(), model, dynamics);
+        let plan = pumped.solve(&horizon, &objective, &x0, None).unwrap().controls;
+        let traj = rollout(&pumped.dynamics, &pumped.model, &x0, plan, 1.0);
+        assert!(traj.predictions.iter().all(|p| p.risk <= 0.5));
+        assert!(traj.controls[0].u[0] + traj.controls[1].u[0] > 0.29);
+    }
+}
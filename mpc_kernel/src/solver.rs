@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::time::Instant;
 use crate::state::{MpcStateSlice, MpcControlSlice};
 use crate::objective::BiocompatObjective;
 
@@ -9,6 +10,12 @@ pub struct MpcHorizonConfig {
     pub max_iterations: usize,
 }
 
+impl Default for MpcHorizonConfig {
+    fn default() -> Self {
+        Self { horizon_steps: 10, dt_seconds: 1.0, max_iterations: 100 }
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum MpcSolveError {
     #[error("invalid horizon configuration")]
@@ -17,14 +24,35 @@ pub enum MpcSolveError {
     Infeasible,
     #[error("internal solver failure")]
     Internal,
+    #[error("deadline passed before a feasible start was found")]
+    Timeout,
+}
+
+/// Plan returned by [`MpcSolver::solve`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MpcSolution {
+    pub controls: Vec<MpcControlSlice>,
+    /// Improvement iterations run, at most `MpcHorizonConfig::max_iterations`.
+    pub iterations: usize,
+    /// The deadline passed before the solver converged; `controls` is the best
+    /// feasible iterate reached by then.
+    pub timed_out: bool,
 }
 
 /// Trait so you can swap underlying QP/NLP solvers without changing runners.[file:39]
+///
+/// Once `deadline` passes a solver stops iterating and returns its best
+/// feasible iterate with `timed_out` set, rather than an error; it fails with
+/// `Timeout` only if it has no feasible iterate yet.
+///
+/// The deadline is checked between units of work, not inside them, so a solve
+/// can overrun it by one such unit; each solver documents its own.
 pub trait MpcSolver {
     fn solve(
         &self,
         cfg: &MpcHorizonConfig,
         obj: &BiocompatObjective,
         x0: &MpcStateSlice,
-    ) -> Result<Vec<MpcControlSlice>, MpcSolveError>;
+        deadline: Option<Instant>,
+    ) -> Result<MpcSolution, MpcSolveError>;
 }
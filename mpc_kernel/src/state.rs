@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use contracts_core::infra::{InfraNodeShardId, InfraNodeShardSnapshot};
+use contracts_core::infra::{InfraNodeShardId, InfraNodeShardSnapshot, ShardStateExtractor};
 
 /// Minimal state slice used by the MPC kernel, extracted from an InfraNodeShard.[file:39]
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -21,3 +21,20 @@ pub trait StateExtractor {
     fn extract_state(&self, snap: &InfraNodeShardSnapshot) -> MpcStateSlice;
     fn extract_control_hint(&self, snap: &InfraNodeShardSnapshot) -> MpcControlSlice;
 }
+
+impl AsRef<[f64]> for MpcControlSlice {
+    fn as_ref(&self) -> &[f64] {
+        &self.u
+    }
+}
+
+/// Default extractor: x is the clipped risk coordinate of each channel, u the normalized actuators.
+impl StateExtractor for ShardStateExtractor {
+    fn extract_state(&self, snap: &InfraNodeShardSnapshot) -> MpcStateSlice {
+        MpcStateSlice { node_id: snap.node_id.clone(), x: self.state_vector(snap) }
+    }
+
+    fn extract_control_hint(&self, snap: &InfraNodeShardSnapshot) -> MpcControlSlice {
+        MpcControlSlice { node_id: snap.node_id.clone(), u: self.control_vector(snap) }
+    }
+}
@@ -0,0 +1,422 @@
+//! Per-step decision log for safety review, written as JSONL, and `replay` to
+//! re-run a log against a solver and report where the decisions diverge.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use ring::digest::{digest, SHA256};
+use contracts_core::bioscale::{BioIntegrationProfile, NeuroRightsSnapshot};
+use contracts_core::infra::{InfraControlCommand, InfraNodeShardId, InfraNodeShardSnapshot};
+use contracts_core::lyapunov::ResidualComputer;
+use contracts_core::metrics::{EcoImpactScalar, KerVector, RiskScalar};
+use mpc_kernel::solver::MpcSolver;
+use mpc_kernel::{BiocompatObjective, DynamicsModel};
+use crate::runner::{FallbackActivation, MpcRuntime, MpcRuntimeError, StepOutcome, ViolationCode};
+
+/// Absolute tolerance when comparing replayed controls and setpoints.
+const REPLAY_TOLERANCE: f64 = 1e-9;
+
+#[derive(Debug, thiserror::Error)]
+pub enum AuditError {
+    #[error("io error on {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("{path}:{line}: malformed decision record: {source}")]
+    Json {
+        path: PathBuf,
+        line: usize,
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+/// Outcome of one named check within a step. A check that did not run is
+/// `skipped` and not `passed`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CheckRecord {
+    pub check: String,
+    pub passed: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub skipped: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// What `MpcRuntime::step_traced` saw while deciding: checks in the order they
+/// ran and the solved control sequence, if the solver was reached.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StepTrace {
+    pub checks: Vec<CheckRecord>,
+    pub controls: Vec<Vec<f64>>,
+}
+
+impl StepTrace {
+    pub(crate) fn passed(&mut self, check: &str, detail: Option<String>) {
+        self.checks.push(CheckRecord { check: check.into(), passed: true, skipped: false, detail });
+    }
+
+    pub(crate) fn failed(&mut self, check: &str, detail: String) {
+        self.checks.push(CheckRecord { check: check.into(), passed: false, skipped: false, detail: Some(detail) });
+    }
+
+    pub(crate) fn skipped(&mut self, check: &str, detail: String) {
+        self.checks.push(CheckRecord { check: check.into(), passed: false, skipped: true, detail: Some(detail) });
+    }
+
+    /// Record `result` under `check` and pass it through.
+    pub(crate) fn outcome<T, E: fmt::Display>(&mut self, check: &str, result: Result<T, E>) -> Result<T, E> {
+        match &result {
+            Ok(_) => self.passed(check, None),
+            Err(e) => self.failed(check, e.to_string()),
+        }
+        result
+    }
+}
+
+/// One line of the decision log.
+///
+/// The snapshot is stored in full so the step can be replayed; the neuro
+/// snapshot and bio profile are not, only whether they were supplied.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DecisionRecord {
+    pub node_id: InfraNodeShardId,
+    pub timestamp: String,
+    /// Hex SHA-256 of the snapshot's JSON encoding.
+    pub snapshot_hash: String,
+    pub snapshot: InfraNodeShardSnapshot,
+    pub ker: KerVector,
+    pub eco: EcoImpactScalar,
+    pub risk: RiskScalar,
+    pub neuro_present: bool,
+    pub bio_profile_present: bool,
+    pub controls: Vec<Vec<f64>>,
+    pub checks: Vec<CheckRecord>,
+    pub command: Option<InfraControlCommand>,
+    pub error: Option<String>,
+    /// Set when `command` came from a fallback.
+    #[serde(default)]
+    pub fallback: Option<FallbackActivation>,
+    /// Class of `error`, see [`MpcRuntimeError::code`].
+    #[serde(default)]
+    pub violation: Option<ViolationCode>,
+    /// The solve hit its deadline, so `controls` are a partial iterate or absent.
+    #[serde(default)]
+    pub timed_out: bool,
+}
+
+/// Hex SHA-256 of the snapshot's JSON encoding.
+pub fn snapshot_hash(snap: &InfraNodeShardSnapshot) -> String {
+    let bytes = serde_json::to_vec(snap).expect("snapshot serializes");
+    hex::encode(digest(&SHA256, &bytes))
+}
+
+/// Append-only JSONL decision log; each record is flushed as it is written.
+pub struct DecisionLog {
+    path: PathBuf,
+    out: BufWriter<File>,
+}
+
+impl DecisionLog {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, AuditError> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|source| AuditError::Io { path: path.clone(), source })?;
+        Ok(Self { path, out: BufWriter::new(file) })
+    }
+
+    pub fn append(&mut self, record: &DecisionRecord) -> Result<(), AuditError> {
+        let line = serde_json::to_string(record).expect("decision record serializes");
+        writeln!(self.out, "{line}")
+            .and_then(|_| self.out.flush())
+            .map_err(|source| AuditError::Io { path: self.path.clone(), source })
+    }
+}
+
+pub fn read_log(path: impl AsRef<Path>) -> Result<Vec<DecisionRecord>, AuditError> {
+    let path = path.as_ref();
+    let data = fs::read_to_string(path).map_err(|source| AuditError::Io { path: path.to_path_buf(), source })?;
+    data.lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(i, l)| {
+            serde_json::from_str(l).map_err(|source| AuditError::Json { path: path.to_path_buf(), line: i + 1, source })
+        })
+        .collect()
+}
+
+impl<S: MpcSolver, D: DynamicsModel, R: ResidualComputer> MpcRuntime<S, D, R> {
+    /// `step`, with the decision appended to `log`. A decision that cannot be
+    /// logged is not returned: a failed write yields `MpcRuntimeError::Audit`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn step_logged(
+        &self,
+        log: &mut DecisionLog,
+        shard: &InfraNodeShardSnapshot,
+        ker: &KerVector,
+        eco: EcoImpactScalar,
+        risk: RiskScalar,
+        neuro: Option<&NeuroRightsSnapshot>,
+        bio_profile: Option<&BioIntegrationProfile>,
+        obj: &BiocompatObjective,
+    ) -> Result<StepOutcome, MpcRuntimeError> {
+        let mut trace = StepTrace::default();
+        let result = self.step_traced(shard, ker, eco, risk, neuro, bio_profile, obj, &mut trace);
+        let record = DecisionRecord {
+            node_id: shard.node_id.clone(),
+            timestamp: shard.timestamp.clone(),
+            snapshot_hash: snapshot_hash(shard),
+            snapshot: shard.clone(),
+            ker: ker.clone(),
+            eco,
+            risk,
+            neuro_present: neuro.is_some(),
+            bio_profile_present: bio_profile.is_some(),
+            controls: trace.controls,
+            checks: trace.checks,
+            command: result.as_ref().ok().map(|o| o.command.clone()),
+            error: result.as_ref().err().map(ToString::to_string),
+            fallback: result.as_ref().ok().and_then(|o| o.fallback.clone()),
+            violation: result.as_ref().err().map(MpcRuntimeError::code),
+            timed_out: result.as_ref().map_or_else(|e| e.code() == ViolationCode::SolverTimeout, |o| o.timed_out),
+        };
+        log.append(&record).map_err(MpcRuntimeError::Audit)?;
+        result
+    }
+}
+
+/// A field of a logged decision that replay did not reproduce.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Divergence {
+    /// 1-based record index in the log.
+    pub record: usize,
+    pub node_id: InfraNodeShardId,
+    pub field: String,
+    pub logged: String,
+    pub replayed: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReplayReport {
+    pub records: usize,
+    pub divergences: Vec<Divergence>,
+}
+
+impl ReplayReport {
+    pub fn is_clean(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+fn close(a: &[f64], b: &[f64]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(x, y)| (x - y).abs() <= REPLAY_TOLERANCE)
+}
+
+fn setpoints(cmd: &Option<InfraControlCommand>) -> Vec<f64> {
+    cmd.iter().flat_map(|c| c.setpoints.iter().map(|s| s.value)).collect()
+}
+
+/// Re-run every decision in the log at `path` through `runtime` and report
+/// fields that differ: snapshot hash, solved controls, check outcomes,
+/// fallback, command and error.
+///
+/// The runtime is replayed from a clean history and with no solve deadline, so
+/// the log should start where the logging runtime did. Two kinds of step cannot
+/// be reproduced and only have the checks before that point compared:
+/// - neuro inputs are not logged, so a step the `bioscale` check rejected is
+///   replayed without them and would go on to solve;
+/// - a step whose solve timed out logged a partial iterate.
+///
+/// Other steps that had neuro inputs have their `bioscale` check skipped.
+pub fn replay<S: MpcSolver, D: DynamicsModel, R: ResidualComputer>(
+    path: impl AsRef<Path>,
+    runtime: MpcRuntime<S, D, R>,
+    obj: &BiocompatObjective,
+) -> Result<ReplayReport, AuditError> {
+    let runtime = runtime.offline();
+    let records = read_log(path)?;
+    let mut report = ReplayReport { records: records.len(), divergences: Vec::new() };
+    for (i, rec) in records.iter().enumerate() {
+        let mut diverge = |field: &str, logged: String, replayed: String| {
+            report.divergences.push(Divergence { record: i + 1, node_id: rec.node_id.clone(), field: field.into(), logged, replayed });
+        };
+
+        let hash = snapshot_hash(&rec.snapshot);
+        if hash != rec.snapshot_hash {
+            diverge("snapshot_hash", rec.snapshot_hash.clone(), hash);
+        }
+
+        let mut trace = StepTrace::default();
+        let result = runtime.step_traced(&rec.snapshot, &rec.ker, rec.eco, rec.risk, None, None, obj, &mut trace);
+
+        let bio_rejected = rec.checks.iter().any(|c| c.check == "bioscale" && !c.passed);
+        let cutoff = if bio_rejected {
+            Some("bioscale")
+        } else if rec.timed_out {
+            Some("solver")
+        } else {
+            None
+        };
+        if let Some(cutoff) = cutoff {
+            let before = |checks: &[CheckRecord]| -> Vec<(String, bool)> {
+                checks.iter().take_while(|c| c.check != cutoff).map(|c| (c.check.clone(), c.passed)).collect()
+            };
+            // Replayed without neuro inputs, a step runs no `bioscale` check at all.
+            let logged = before(&rec.checks);
+            let replayed: Vec<_> = before(&trace.checks).into_iter().take(logged.len()).collect();
+            if logged != replayed {
+                diverge("checks", format!("{logged:?}"), format!("{replayed:?}"));
+            }
+            continue;
+        }
+
+        if rec.controls.len() != trace.controls.len()
+            || rec.controls.iter().zip(&trace.controls).any(|(a, b)| !close(a, b))
+        {
+            diverge("controls", format!("{:?}", rec.controls), format!("{:?}", trace.controls));
+        }
+
+        let outcomes = |checks: &[CheckRecord]| -> Vec<(String, bool)> {
+            checks
+                .iter()
+                .filter(|c| !(rec.neuro_present && c.check == "bioscale"))
+                .map(|c| (c.check.clone(), c.passed))
+                .collect()
+        };
+        let (logged, replayed) = (outcomes(&rec.checks), outcomes(&trace.checks));
+        if logged != replayed {
+            diverge("checks", format!("{logged:?}"), format!("{replayed:?}"));
+        }
+
+        let command = result.as_ref().ok().map(|o| o.command.clone());
+        let fallback = result.as_ref().ok().and_then(|o| o.fallback.as_ref()).map(|f| f.action);
+        let logged_fallback = rec.fallback.as_ref().map(|f| f.action);
+        if logged_fallback != fallback {
+            diverge("fallback", format!("{logged_fallback:?}"), format!("{fallback:?}"));
+        }
+        if rec.command.is_some() != command.is_some() || !close(&setpoints(&rec.command), &setpoints(&command)) {
+            diverge("command", format!("{:?}", setpoints(&rec.command)), format!("{:?}", setpoints(&command)));
+        }
+        let error = result.err().map(|e| e.to_string());
+        if rec.error != error {
+            diverge("error", format!("{:?}", rec.error), format!("{error:?}"));
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mpc_kernel::ObjectiveTermWeights;
+    use crate::runner::{FallbackAction, MpcRuntimeConfig};
+    use crate::test_support::{self, basin, config, sla_model, PgRuntime};
+
+    /// The basin with a gold limit at 6 m and a legal one at 9 m.
+    fn shard(level: f64) -> InfraNodeShardSnapshot {
+        let mut snap = basin(level);
+        snap.channels[0].band.gold_limit = Some(6.0);
+        snap.channels[0].band.legal_limit = Some(9.0);
+        snap
+    }
+
+    fn runtime(sla_gain: f64) -> PgRuntime {
+        runtime_with_budget(sla_gain, None)
+    }
+
+    /// SLA misses fall with pump speed at the given rate.
+    fn runtime_with_budget(sla_gain: f64, solve_budget_seconds: Option<f64>) -> PgRuntime {
+        test_support::runtime(MpcRuntimeConfig { solve_budget_seconds, ..config(2) }, sla_model(sla_gain))
+    }
+
+    fn objective() -> BiocompatObjective {
+        test_support::objective(ObjectiveTermWeights { lambda_slaviolation: 1.0, lambda_effort: 0.5, ..Default::default() })
+    }
+
+    fn write_log(path: &Path) {
+        let (rt, obj, ker) = (runtime(1.0), objective(), KerVector::default());
+        let mut log = DecisionLog::open(path).unwrap();
+        // Soft band (derated), then hard band (stopped).
+        rt.step_logged(&mut log, &shard(7.5), &ker, 0.6, 0.1, None, None, &obj).unwrap();
+        let stopped = rt.step_logged(&mut log, &shard(9.5), &ker, 0.6, 0.1, None, None, &obj).unwrap();
+        assert_eq!(stopped.fallback.map(|f| f.action), Some(FallbackAction::Stop));
+    }
+
+    #[test]
+    fn test_log_records_decisions_and_replays_clean() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("decisions.jsonl");
+        write_log(&path);
+
+        let records = read_log(&path).unwrap();
+        assert_eq!(records.len(), 2);
+        let derated = &records[0];
+        assert_eq!(derated.snapshot_hash, snapshot_hash(&shard(7.5)));
+        assert_eq!(derated.controls.len(), 2);
+        let names: Vec<&str> = derated.checks.iter().map(|c| c.check.as_str()).collect();
+        assert_eq!(names, ["corridor", "solver", "lyapunov"]);
+        assert!(derated.checks[0].detail.as_deref().unwrap().starts_with("derate factor=0.5"));
+        assert!(derated.command.is_some() && derated.error.is_none());
+
+        let stopped = &records[1];
+        assert!(stopped.controls.is_empty() && stopped.error.is_none());
+        assert!(stopped.command.as_ref().unwrap().setpoints.iter().all(|sp| sp.value == 0.0));
+        assert!(!stopped.checks[0].passed && stopped.checks[0].detail.as_deref().unwrap().contains("legal"));
+        let f = stopped.fallback.as_ref().unwrap();
+        assert_eq!((f.action, f.trigger), (FallbackAction::Stop, ViolationCode::CorridorHardBand));
+
+        // Replay ignores the solve budget, so even a zero one reproduces the log.
+        let report = replay(&path, runtime_with_budget(1.0, Some(0.0)), &objective()).unwrap();
+        assert_eq!(report.records, 2);
+        assert!(report.is_clean(), "{:?}", report.divergences);
+    }
+
+    #[test]
+    fn test_replay_reports_divergence() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("decisions.jsonl");
+        write_log(&path);
+
+        // A weaker SLA response makes the retuned solver pump less.
+        let report = replay(&path, runtime(0.6), &objective()).unwrap();
+        let fields: Vec<(usize, &str)> = report.divergences.iter().map(|d| (d.record, d.field.as_str())).collect();
+        assert_eq!(fields, [(1, "controls"), (1, "command")]);
+
+        // Editing the stored snapshot breaks its hash.
+        let edited = fs::read_to_string(&path).unwrap().replacen("\"value\":7.5", "\"value\":7.4", 1);
+        fs::write(&path, edited).unwrap();
+        let report = replay(&path, runtime(1.0), &objective()).unwrap();
+        assert!(report.divergences.iter().any(|d| d.record == 1 && d.field == "snapshot_hash"));
+    }
+
+    #[test]
+    fn test_replay_skips_steps_it_cannot_reproduce() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("decisions.jsonl");
+        let (obj, ker) = (objective(), KerVector::default());
+        let mut log = DecisionLog::open(&path).unwrap();
+
+        // Rejected on neuro inputs, which the log does not keep.
+        let neuro = NeuroRightsSnapshot { normalized_risk: 0.7, eco_impact_index: 0.5 };
+        let profile = BioIntegrationProfile::default();
+        let rejected = runtime(1.0).step_logged(&mut log, &shard(5.0), &ker, 0.6, 0.1, Some(&neuro), Some(&profile), &obj);
+        assert!(rejected.is_err());
+
+        // A zero budget leaves the solve at its first iterate.
+        let rt = runtime_with_budget(1.0, Some(0.0));
+        assert!(rt.step_logged(&mut log, &shard(5.0), &ker, 0.6, 0.1, None, None, &obj).unwrap().timed_out);
+        drop(log);
+
+        let records = read_log(&path).unwrap();
+        assert!(!records[0].timed_out && records[1].timed_out);
+        let report = replay(&path, runtime_with_budget(1.0, Some(0.0)), &obj).unwrap();
+        assert!(report.is_clean(), "{:?}", report.divergences);
+    }
+}
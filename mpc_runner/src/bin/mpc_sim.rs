@@ -0,0 +1,23 @@
+//! Offline closed-loop simulation: `mpc_sim <scenario.toml> <trace.csv>`.
+
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let [scenario, trace] = args.as_slice() else {
+        eprintln!("usage: mpc_sim <scenario.toml> <trace.csv>");
+        return ExitCode::from(2);
+    };
+    match mpc_runner::sim::run_scenario_file(scenario, trace) {
+        Ok(rows) => {
+            let violations = rows.iter().filter(|r| r.violation.is_some()).count();
+            let (first, last) = (rows.first().map_or(0.0, |r| r.v_t), rows.last().map_or(0.0, |r| r.v_t));
+            println!("{} steps, V_t {first:.4} -> {last:.4}, {violations} violation(s); trace in {trace}", rows.len());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("mpc_sim: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,420 @@
+//! Fleet-level coordination: step many shards together, enforce shared gate
+//! flow limits across nodes, and hold the whole fleet move to the global
+//! Lyapunov contract (K_E_R_Grammar.md E.2).
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use contracts_core::bioscale::{BioIntegrationProfile, NeuroRightsSnapshot};
+use contracts_core::infra::{InfraControlCommand, InfraNodeShardId, InfraNodeShardSnapshot};
+use contracts_core::lyapunov::{GlobalResidual, ResidualComputer, WeightedSumResidual};
+use contracts_core::metrics::{EcoImpactScalar, KerVector, RiskScalar};
+use mpc_constraints::{LyapunovResidualChecker, LyapunovViolation};
+use mpc_kernel::solver::MpcSolver;
+use mpc_kernel::{BiocompatObjective, DynamicsModel, HoldDynamics};
+use crate::runner::{FallbackAction, FallbackActivation, LyapunovFallback, MpcRuntime, MpcRuntimeError, ViolationCode};
+
+#[derive(Debug, thiserror::Error)]
+pub enum FleetError {
+    #[error("io error on {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("{path}:{line}: {message}")]
+    Csv { path: PathBuf, line: usize, message: String },
+    #[error("no runtime registered for node {0}")]
+    UnknownNode(InfraNodeShardId),
+    #[error("node {node_id}: {source}")]
+    Node {
+        node_id: InfraNodeShardId,
+        #[source]
+        source: MpcRuntimeError,
+    },
+    #[error("fleet Lyapunov violation: {0}")]
+    Lyapunov(#[source] LyapunovViolation),
+}
+
+impl FleetError {
+    /// A node's error keeps its own code.
+    pub fn code(&self) -> ViolationCode {
+        match self {
+            FleetError::Io { .. } | FleetError::Csv { .. } => ViolationCode::FleetGateTable,
+            FleetError::UnknownNode(_) => ViolationCode::FleetUnknownNode,
+            FleetError::Node { source, .. } => source.code(),
+            FleetError::Lyapunov(_) => ViolationCode::FleetLyapunovIncrease,
+        }
+    }
+}
+
+/// One row of the gate table (`config/tempe_phoenix_gates.csv`).
+///
+/// A gate is commanded through the shard control whose `name` equals the gate
+/// name, with its setpoint in m³/s.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GateLimit {
+    pub gate_id: u32,
+    pub name: String,
+    pub from_node: u32,
+    pub to_node: u32,
+    pub max_flow_m3s: f64,
+    pub group_id: u32,
+}
+
+const GATE_HEADER: &str = "gate_id,name,from_node,to_node,max_flow_m3s,group_id";
+
+/// Load the gate table; blank lines between groups are skipped.
+pub fn load_gates(path: impl AsRef<Path>) -> Result<Vec<GateLimit>, FleetError> {
+    let path = path.as_ref();
+    let data = fs::read_to_string(path).map_err(|source| FleetError::Io { path: path.to_path_buf(), source })?;
+    let csv_err = |line: usize, message: String| FleetError::Csv { path: path.to_path_buf(), line, message };
+    let mut lines = data.lines().enumerate().filter(|(_, l)| !l.trim().is_empty());
+    match lines.next() {
+        Some((_, header)) if header.trim() == GATE_HEADER => {}
+        _ => return Err(csv_err(1, format!("expected header `{GATE_HEADER}`"))),
+    }
+    lines
+        .map(|(i, line)| {
+            let f: Vec<&str> = line.split(',').map(str::trim).collect();
+            if f.len() != 6 {
+                return Err(csv_err(i + 1, format!("expected 6 fields, found {}", f.len())));
+            }
+            let num = |j: usize| f[j].parse::<u32>().map_err(|e| csv_err(i + 1, format!("field {j}: {e}")));
+            let max_flow_m3s: f64 = f[4].parse().map_err(|e| csv_err(i + 1, format!("max_flow_m3s: {e}")))?;
+            if !max_flow_m3s.is_finite() || max_flow_m3s < 0.0 {
+                return Err(csv_err(i + 1, format!("invalid max_flow_m3s {max_flow_m3s}")));
+            }
+            Ok(GateLimit {
+                gate_id: num(0)?,
+                name: f[1].to_string(),
+                from_node: num(2)?,
+                to_node: num(3)?,
+                max_flow_m3s,
+                group_id: num(5)?,
+            })
+        })
+        .collect()
+}
+
+/// Flow constraints shared by several nodes.
+///
+/// Every gate is capped at its own `max_flow_m3s`. A group listed in
+/// `group_max_flow_m3s` is additionally capped on the summed flow of its gates,
+/// which may sit on different shards (e.g. the WTP turnouts of group 4).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FlowCoupling {
+    pub gates: Vec<GateLimit>,
+    #[serde(default)]
+    pub group_max_flow_m3s: BTreeMap<u32, f64>,
+}
+
+impl FlowCoupling {
+    pub fn from_gates_csv(path: impl AsRef<Path>) -> Result<Self, FleetError> {
+        Ok(Self { gates: load_gates(path)?, group_max_flow_m3s: BTreeMap::new() })
+    }
+
+    pub fn with_group_limit(mut self, group_id: u32, max_flow_m3s: f64) -> Self {
+        self.group_max_flow_m3s.insert(group_id, max_flow_m3s);
+        self
+    }
+
+    /// Clamp gate setpoints to their own limit, then scale every gate of an
+    /// over-limit group by the same factor. Returns the groups that were scaled.
+    pub fn enforce(&self, commands: &mut [InfraControlCommand]) -> Vec<u32> {
+        for gate in &self.gates {
+            for sp in commands.iter_mut().flat_map(|c| &mut c.setpoints).filter(|sp| sp.name == gate.name) {
+                sp.value = sp.value.clamp(0.0, gate.max_flow_m3s);
+            }
+        }
+        let mut scaled = Vec::new();
+        for (&group, &cap) in &self.group_max_flow_m3s {
+            let names: Vec<&str> =
+                self.gates.iter().filter(|g| g.group_id == group).map(|g| g.name.as_str()).collect();
+            let mut members: Vec<&mut f64> = commands
+                .iter_mut()
+                .flat_map(|c| &mut c.setpoints)
+                .filter(|sp| names.contains(&sp.name.as_str()))
+                .map(|sp| &mut sp.value)
+                .collect();
+            let total: f64 = members.iter().map(|v| **v).sum();
+            if total > cap {
+                let factor = if total > 0.0 { cap.max(0.0) / total } else { 0.0 };
+                for v in &mut members {
+                    **v *= factor;
+                }
+                scaled.push(group);
+            }
+        }
+        scaled
+    }
+}
+
+/// Per-node inputs to one fleet step.
+#[derive(Clone, Copy, Debug)]
+pub struct FleetMember<'a> {
+    pub shard: &'a InfraNodeShardSnapshot,
+    pub ker: &'a KerVector,
+    pub eco: EcoImpactScalar,
+    pub risk: RiskScalar,
+    pub neuro: Option<&'a NeuroRightsSnapshot>,
+    pub bio_profile: Option<&'a BioIntegrationProfile>,
+}
+
+/// Result of one fleet step.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FleetStep {
+    /// One command per member, in input order.
+    pub commands: Vec<InfraControlCommand>,
+    /// Gate groups whose flows were scaled down to their shared limit.
+    pub scaled_groups: Vec<u32>,
+    /// Global residual before the move and predicted after it.
+    pub v_t: f64,
+    pub v_next: f64,
+    /// The fleet move failed a check and every node was held, except those
+    /// whose own runtime stopped them.
+    pub held: bool,
+    /// Nodes whose own runtime fell back. When `held`, only a
+    /// [`FallbackAction::Stop`] keeps its command; the others are held.
+    #[serde(default)]
+    pub fallbacks: BTreeMap<InfraNodeShardId, FallbackActivation>,
+}
+
+/// Runs one [`MpcRuntime`] per node and coordinates their moves.
+///
+/// Each node is first solved on its own, so it still enforces its local
+/// corridors and Lyapunov check. The fleet then applies the shared gate limits,
+/// re-runs those node checks on every command the limits changed, and checks the
+/// predicted global residual of the combined move, which catches moves that are
+/// fine per node but not once neighbours' limits are applied.
+pub struct FleetRuntime<S: MpcSolver, D: DynamicsModel = HoldDynamics, R: ResidualComputer = WeightedSumResidual> {
+    nodes: BTreeMap<InfraNodeShardId, MpcRuntime<S, D, R>>,
+    coupling: FlowCoupling,
+    lyap_check: LyapunovResidualChecker,
+    fallback: LyapunovFallback,
+    resid: R,
+}
+
+impl<S: MpcSolver, D: DynamicsModel, R: ResidualComputer> FleetRuntime<S, D, R> {
+    pub fn new(
+        coupling: FlowCoupling,
+        lyap_check: LyapunovResidualChecker,
+        fallback: LyapunovFallback,
+        resid: R,
+    ) -> Self {
+        Self { nodes: BTreeMap::new(), coupling, lyap_check, fallback, resid }
+    }
+
+    pub fn with_node(mut self, node_id: impl Into<InfraNodeShardId>, runtime: MpcRuntime<S, D, R>) -> Self {
+        self.nodes.insert(node_id.into(), runtime);
+        self
+    }
+
+    pub fn step(&self, members: &[FleetMember<'_>], obj: &BiocompatObjective) -> Result<FleetStep, FleetError> {
+        let runtime = |m: &FleetMember<'_>| {
+            self.nodes.get(&m.shard.node_id).ok_or_else(|| FleetError::UnknownNode(m.shard.node_id.clone()))
+        };
+
+        let mut commands = Vec::with_capacity(members.len());
+        let mut fallbacks = BTreeMap::new();
+        for m in members {
+            let out = runtime(m)?
+                .step(m.shard, m.ker, m.eco, m.risk, m.neuro, m.bio_profile, obj)
+                .map_err(|source| FleetError::Node { node_id: m.shard.node_id.clone(), source })?;
+            if let Some(f) = out.fallback {
+                fallbacks.insert(m.shard.node_id.clone(), f);
+            }
+            commands.push(out.command);
+        }
+        let solved = commands.clone();
+        let scaled_groups = self.coupling.enforce(&mut commands);
+
+        // Re-run the node checks on every command the shared limits changed.
+        let recheck = |commands: &mut [InfraControlCommand], unlimited: &[InfraControlCommand]| {
+            for ((m, cmd), unlimited) in members.iter().zip(commands).zip(unlimited) {
+                if cmd != unlimited {
+                    runtime(m)?
+                        .recheck_command(m.shard, cmd)
+                        .map_err(|source| FleetError::Node { node_id: m.shard.node_id.clone(), source })?;
+                }
+            }
+            Ok::<_, FleetError>(())
+        };
+        let before = GlobalResidual::from_snapshots(members.iter().map(|m| m.shard));
+        let v_t = self.resid.value(&before);
+        // Predict the residual after `commands` and check it against `before`.
+        let check = |commands: &[InfraControlCommand]| -> Result<GlobalResidual, FleetError> {
+            let mut after = GlobalResidual::default();
+            for (m, cmd) in members.iter().zip(commands) {
+                after.terms.extend(runtime(m)?.predict_residual(m.shard, cmd).terms);
+            }
+            self.lyap_check.check_step(&self.resid, &before, &after).map_err(FleetError::Lyapunov)?;
+            Ok(after)
+        };
+
+        match recheck(&mut commands, &solved).and_then(|()| check(&commands)) {
+            Ok(after) => Ok(FleetStep { commands, scaled_groups, v_t, v_next: self.resid.value(&after), held: false, fallbacks }),
+            Err(e) => match self.fallback {
+                LyapunovFallback::Reject => Err(e),
+                // A node's own stop stands; an empty control vector keeps every
+                // other actuator at its current setting. The hold is held to the
+                // shared limits and must not raise the residual either.
+                LyapunovFallback::Hold => {
+                    let unlimited: Vec<_> = members
+                        .iter()
+                        .zip(solved)
+                        .map(|(m, solved)| match fallbacks.get(&m.shard.node_id) {
+                            Some(f) if f.action == FallbackAction::Stop => solved,
+                            _ => m.shard.control_from_mpc(&[][..]),
+                        })
+                        .collect();
+                    let mut commands = unlimited.clone();
+                    let scaled_groups = self.coupling.enforce(&mut commands);
+                    recheck(&mut commands, &unlimited)?;
+                    let after = check(&commands)?;
+                    Ok(FleetStep { commands, scaled_groups, v_t, v_next: self.resid.value(&after), held: true, fallbacks })
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use contracts_core::infra::ControlChannel;
+    use mpc_kernel::{
+        AffineKerModel, BiocompatObjectiveConfig, LinearStateSpace, ObjectiveTermWeights, ProjectedGradientSolver,
+    };
+    use crate::runner::{MpcRuntimeConfig, SafeStateRamp, SolverFallback};
+    use crate::test_support::{self, config, sla_model};
+
+    fn gates_csv() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../config/tempe_phoenix_gates.csv")
+    }
+
+    /// A canal junction feeding one WTP turnout gate.
+    fn shard(id: &str, gate: &str) -> InfraNodeShardSnapshot {
+        let turnout = ControlChannel { name: gate.into(), unit: "m3/s".into(), min: 0.0, max: 80.0, current: 20.0 };
+        test_support::shard(id, "stage", 5.0, turnout)
+    }
+
+    type Runtime<D> = MpcRuntime<ProjectedGradientSolver<AffineKerModel>, D>;
+
+    /// Delivering water cuts SLA misses, so each node wants its gate fully open.
+    fn node<D: DynamicsModel>(dynamics: D, solver_fallback: SolverFallback) -> Runtime<D> {
+        test_support::runtime(MpcRuntimeConfig { solver_fallback, ..config(2) }, sla_model(1.0))
+            .with_prediction(dynamics, WeightedSumResidual)
+    }
+
+    fn objective() -> BiocompatObjective {
+        test_support::objective(ObjectiveTermWeights { lambda_slaviolation: 1.0, ..Default::default() })
+    }
+
+    fn fleet<D: DynamicsModel + Clone>(dynamics: D, fallback: LyapunovFallback) -> FleetRuntime<ProjectedGradientSolver<AffineKerModel>, D> {
+        let coupling = FlowCoupling::from_gates_csv(gates_csv()).unwrap().with_group_limit(4, 100.0);
+        FleetRuntime::new(coupling, LyapunovResidualChecker { allow_equal: true }, fallback, WeightedSumResidual)
+            .with_node("SRP_Canal_J1", node(dynamics.clone(), SolverFallback::Reject))
+            .with_node("SRP_Canal_J2", node(dynamics, SolverFallback::Reject))
+    }
+
+    fn junctions() -> (InfraNodeShardSnapshot, InfraNodeShardSnapshot) {
+        (shard("SRP_Canal_J1", "Canal_Turnout_E_to_WTP_E"), shard("SRP_Canal_J2", "Canal_Turnout_C_to_WTP_C"))
+    }
+
+    fn run<D: DynamicsModel + Clone>(
+        fleet: &FleetRuntime<ProjectedGradientSolver<AffineKerModel>, D>,
+        obj: &BiocompatObjective,
+    ) -> Result<FleetStep, FleetError> {
+        let (east, central) = junctions();
+        run_on(fleet, obj, &east, &central)
+    }
+
+    fn run_on<D: DynamicsModel + Clone>(
+        fleet: &FleetRuntime<ProjectedGradientSolver<AffineKerModel>, D>,
+        obj: &BiocompatObjective,
+        east: &InfraNodeShardSnapshot,
+        central: &InfraNodeShardSnapshot,
+    ) -> Result<FleetStep, FleetError> {
+        let ker = KerVector::default();
+        let member = |s| FleetMember { shard: s, ker: &ker, eco: 0.6, risk: 0.1, neuro: None, bio_profile: None };
+        fleet.step(&[member(east), member(central)], obj)
+    }
+
+    #[test]
+    fn test_load_repo_gates() {
+        let gates = load_gates(gates_csv()).unwrap();
+        assert_eq!(gates.len(), 12);
+        assert_eq!(gates[6].name, "Canal_Turnout_E_to_WTP_E");
+        assert_eq!((gates[6].from_node, gates[6].max_flow_m3s, gates[6].group_id), (10, 80.0, 4));
+    }
+
+    #[test]
+    fn test_group_limit_shared_across_nodes() {
+        // Both turnouts want 80 m³/s; group 4 allows 100 in total.
+        let step = run(&fleet(HoldDynamics, LyapunovFallback::Reject), &objective()).unwrap();
+        let flows: Vec<f64> = step.commands.iter().map(|c| c.setpoints[0].value).collect();
+        assert!(flows.iter().all(|f| (f - 50.0).abs() < 1e-6), "{flows:?}");
+        assert_eq!(step.scaled_groups, vec![4]);
+        assert!(!step.held);
+    }
+
+    #[test]
+    fn test_fleet_move_must_not_raise_residual() {
+        // Stage rises 0.7/s and each unit of normalized flow drains 1/s: fully open
+        // is stable per node, but the shared cap (u = 0.625) lets both stages rise,
+        // which the node's own check catches when re-run on the scaled command.
+        let dynamics = LinearStateSpace::new(vec![vec![0.0]], vec![vec![-1.0]], vec![0.7]).unwrap();
+        let err = run(&fleet(dynamics.clone(), LyapunovFallback::Reject), &objective()).unwrap_err();
+        assert!(
+            matches!(&err, FleetError::Node { node_id, source: MpcRuntimeError::Lyapunov { step: 1, .. } }
+                if *node_id == InfraNodeShardId::from("SRP_Canal_J1")),
+            "{err}"
+        );
+        assert_eq!(err.code(), ViolationCode::LyapunovIncrease);
+
+        // Holding the current 20 m³/s drains even less, so the fleet cannot hold.
+        assert!(matches!(run(&fleet(dynamics, LyapunovFallback::Hold), &objective()), Err(FleetError::Lyapunov(_))));
+    }
+
+    #[test]
+    fn test_fleet_hold_keeps_stops_and_limits() {
+        // Stage rises 0.2/s, so holding 20 of 80 m³/s (u = 0.25) drains it. No plan
+        // meets the eco floor, and each node's ramp towards closed lets it rise.
+        let dynamics = LinearStateSpace::new(vec![vec![0.0]], vec![vec![-1.0]], vec![0.2]).unwrap();
+        let hold_fleet = |coupling: FlowCoupling, max_delta: f64| {
+            let ramp = SolverFallback::RampToSafe(SafeStateRamp::new(vec![0.0], max_delta).unwrap());
+            let checker = LyapunovResidualChecker { allow_equal: true };
+            FleetRuntime::new(coupling, checker, LyapunovFallback::Hold, WeightedSumResidual)
+                .with_node("SRP_Canal_J1", node(dynamics.clone(), ramp.clone()))
+                .with_node("SRP_Canal_J2", node(dynamics.clone(), ramp))
+        };
+        let strict = BiocompatObjective::new_checked(BiocompatObjectiveConfig { e_min: 0.7, ..objective().config().clone() }).unwrap();
+
+        let held = run(&hold_fleet(FlowCoupling::default(), 0.1), &strict).unwrap();
+        assert!(held.held && held.scaled_groups.is_empty());
+        assert!(held.v_next < held.v_t, "{held:?}");
+        assert!(held.commands.iter().all(|c| c.setpoints[0].value == 20.0));
+        assert_eq!(held.fallbacks.len(), 2);
+        assert!(held.fallbacks.values().all(|f| f.action == FallbackAction::RampToSafe));
+
+        // The held 40 m³/s is over a 36 m³/s group cap, so the hold is scaled too.
+        let capped = FlowCoupling::from_gates_csv(gates_csv()).unwrap().with_group_limit(4, 36.0);
+        let held = run(&hold_fleet(capped, 0.1), &strict).unwrap();
+        assert!(held.held && held.v_next < held.v_t, "{held:?}");
+        assert_eq!(held.scaled_groups, vec![4]);
+        assert!(held.commands.iter().all(|c| (c.setpoints[0].value - 18.0).abs() < 1e-9), "{held:?}");
+
+        // J1 is past its legal limit and stopped; J2 ramps from 60 to 12 m³/s, so
+        // the fleet holds J2 and keeps J1 stopped.
+        let (mut east, mut central) = junctions();
+        east.channels[0].band.legal_limit = Some(4.0);
+        central.controls[0].current = 60.0;
+        let held = run_on(&hold_fleet(FlowCoupling::default(), 0.6), &strict, &east, &central).unwrap();
+        assert!(held.held && held.v_next < held.v_t, "{held:?}");
+        let flows: Vec<f64> = held.commands.iter().map(|c| c.setpoints[0].value).collect();
+        assert_eq!(flows, vec![0.0, 60.0]);
+        assert_eq!(held.fallbacks[&InfraNodeShardId::from("SRP_Canal_J1")].action, FallbackAction::Stop);
+    }
+}
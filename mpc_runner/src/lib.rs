@@ -1,3 +1,17 @@
 pub mod runner;
+pub mod sim;
+pub mod fleet;
+pub mod audit;
+pub mod timing;
+#[cfg(test)]
+mod test_support;
 
-pub use runner::{MpcRuntime, MpcRuntimeConfig, MpcRuntimeError};
+pub use runner::{
+    MpcRuntime, MpcRuntimeConfig, MpcRuntimeError, ViolationCode, LyapunovFallback, DerateMode, SolverFallback,
+    SafeStateRamp, SafeStateRampError,
+    FallbackAction, FallbackActivation, StepOutcome,
+};
+pub use sim::{simulate, run_scenario_file, PlantModel, Scenario, SimError, StateSpacePlant, TraceRow};
+pub use fleet::{load_gates, FleetError, FleetMember, FleetRuntime, FleetStep, FlowCoupling, GateLimit};
+pub use audit::{replay, read_log, AuditError, CheckRecord, DecisionLog, DecisionRecord, Divergence, ReplayReport, StepTrace};
+pub use timing::{SolveStats, SOLVE_TIME_WINDOW};
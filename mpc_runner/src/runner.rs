@@ -1,39 +1,315 @@
 use serde::{Deserialize, Serialize};
-use contracts_core::infra::{InfraNodeShardSnapshot, InfraControlCommand};
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use contracts_core::infra::{ControlChannel, InfraNodeShardSnapshot, InfraControlCommand};
 use contracts_core::metrics::{KerVector, EcoImpactScalar, RiskScalar};
 use contracts_core::bioscale::{NeuroRightsSnapshot, BioIntegrationProfile};
-use mpc_kernel::{MpcStateSlice, MpcControlSlice, MpcHorizonConfig, BiocompatObjective};
+use contracts_core::lyapunov::{GlobalResidual, ResidualComputer, WeightedSumResidual};
+use mpc_kernel::{
+    MpcStateSlice, MpcControlSlice, MpcHorizonConfig, BiocompatObjective, StateExtractor,
+    DynamicsModel, HoldDynamics,
+};
 use mpc_constraints::{
     CorridorCheck,
+    CorridorDecision,
+    CorridorViolation,
     LyapunovResidualChecker,
+    LyapunovViolation,
     BiocompatGuard,
+    BiocompatViolation,
 };
 use mpc_kernel::solver::{MpcSolver, MpcSolveError};
+use crate::audit::{AuditError, StepTrace};
+use crate::timing::{SolveStats, SolveTimes};
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct MpcRuntimeConfig {
     pub horizon: MpcHorizonConfig,
+    /// What `step` does when the planned trajectory would raise the residual.
+    #[serde(default)]
+    pub lyapunov_fallback: LyapunovFallback,
+    /// How a soft-band derate factor is applied to the solved move.
+    #[serde(default)]
+    pub derate: DerateMode,
+    /// What `step` commands when the solver fails.
+    #[serde(default)]
+    pub solver_fallback: SolverFallback,
+    /// Wall-clock budget for each solve; one control period
+    /// (`horizon.dt_seconds`) when unset. Simulation and replay run without one.
+    #[serde(default)]
+    pub solve_budget_seconds: Option<f64>,
+}
+
+/// Applying a corridor derate factor `f` to each normalized control.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DerateMode {
+    /// `u * f`.
+    #[default]
+    Scale,
+    /// `min(u, f)`; controls already below the factor are untouched.
+    Clamp,
+}
+
+impl DerateMode {
+    pub fn apply(self, u: &mut MpcControlSlice, factor: f64) {
+        let factor = factor.clamp(0.0, 1.0);
+        for ui in &mut u.u {
+            *ui = match self {
+                DerateMode::Scale => *ui * factor,
+                DerateMode::Clamp => ui.min(factor),
+            };
+        }
+    }
+}
+
+/// Response to a plan whose predicted residual rises at some step of the horizon.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LyapunovFallback {
+    /// Return `MpcRuntimeError::Lyapunov`.
+    #[default]
+    Reject,
+    /// Keep every actuator at its current setpoint, derated like a solved move,
+    /// provided the residual predicted for holding does not rise either.
+    Hold,
+}
+
+/// Command issued when the solver returns an error or an empty plan.
+///
+/// Fallback commands are derated by the corridor like a solved move, but are
+/// the declared safe action, so they are not held to the Lyapunov contract.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "policy", rename_all = "snake_case")]
+pub enum SolverFallback {
+    /// Return the solver error; the node gets no command.
+    #[default]
+    Reject,
+    /// Repeat the last command that passed every check on this runtime, or the
+    /// current setpoints before there is one.
+    HoldLastSafe,
+    /// Move each actuator towards a documented safe state; see [`SafeStateRamp`].
+    RampToSafe(SafeStateRamp),
+    /// Command the shard's minimum-risk hint (`StateExtractor::extract_control_hint`).
+    ShardHint,
+}
+
+/// Move each actuator from its current setting towards `safe_state`
+/// (normalized, in actuator order) by at most `max_delta` per step. Actuators
+/// past the end of `safe_state` are held.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "SafeStateRampFields")]
+pub struct SafeStateRamp {
+    safe_state: Vec<f64>,
+    max_delta: f64,
+}
+
+#[derive(Deserialize)]
+struct SafeStateRampFields {
+    safe_state: Vec<f64>,
+    max_delta: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum SafeStateRampError {
+    #[error("max_delta must be finite and positive, got {0}")]
+    InvalidMaxDelta(f64),
+    #[error("safe_state[{index}] = {value} is outside [0, 1]")]
+    InvalidSafeState { index: usize, value: f64 },
+}
+
+impl SafeStateRamp {
+    pub fn new(safe_state: Vec<f64>, max_delta: f64) -> Result<Self, SafeStateRampError> {
+        if !(max_delta.is_finite() && max_delta > 0.0) {
+            return Err(SafeStateRampError::InvalidMaxDelta(max_delta));
+        }
+        if let Some((index, &value)) = safe_state.iter().enumerate().find(|(_, v)| !(0.0..=1.0).contains(*v)) {
+            return Err(SafeStateRampError::InvalidSafeState { index, value });
+        }
+        Ok(Self { safe_state, max_delta })
+    }
+
+    pub fn safe_state(&self) -> &[f64] {
+        &self.safe_state
+    }
+
+    pub fn max_delta(&self) -> f64 {
+        self.max_delta
+    }
+
+    /// One step of the ramp from the normalized setting `current`.
+    pub fn next(&self, current: &[f64]) -> Vec<f64> {
+        let step = self.max_delta;
+        current
+            .iter()
+            .enumerate()
+            .map(|(i, &c)| self.safe_state.get(i).map_or(c, |&safe| c + (safe - c).clamp(-step, step)))
+            .collect()
+    }
+}
+
+impl TryFrom<SafeStateRampFields> for SafeStateRamp {
+    type Error = SafeStateRampError;
+
+    fn try_from(f: SafeStateRampFields) -> Result<Self, Self::Error> {
+        Self::new(f.safe_state, f.max_delta)
+    }
+}
+
+/// Which fallback produced a command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FallbackAction {
+    /// A channel crossed its hard band: every actuator commanded to its minimum.
+    Stop,
+    /// `LyapunovFallback::Hold`: every actuator kept at its current setpoint.
+    HoldCurrent,
+    HoldLastSafe,
+    RampToSafe,
+    ShardHint,
+}
+
+/// A step whose command came from a fallback rather than the solved plan.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FallbackActivation {
+    pub action: FallbackAction,
+    /// Class of the error that triggered the fallback.
+    pub trigger: ViolationCode,
+    pub reason: String,
+}
+
+impl fmt::Display for FallbackActivation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "fallback {:?} after {}: {}", self.action, self.trigger, self.reason)
+    }
+}
+
+/// Result of one `MpcRuntime::step`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StepOutcome {
+    pub command: InfraControlCommand,
+    /// Set when the command is a fallback.
+    pub fallback: Option<FallbackActivation>,
+    /// The solve hit its deadline: the move is its best iterate so far, or a
+    /// fallback if it had no feasible one yet.
+    #[serde(default)]
+    pub timed_out: bool,
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum MpcRuntimeError {
-    #[error("corridor violation: {0}")]
-    Corridor(String),
-    #[error("Lyapunov violation: {0}")]
-    Lyapunov(String),
+    /// Every channel past its hard band, in channel order.
+    #[error("corridor violation: {}", joined(.violations))]
+    Corridor { violations: Vec<CorridorViolation> },
+    /// `step` is 1 for the state after the applied move.
+    #[error("Lyapunov violation at predicted step {step}: {source}")]
+    Lyapunov {
+        step: usize,
+        #[source]
+        source: LyapunovViolation,
+    },
     #[error("bioscale violation: {0}")]
-    Biocompat(String),
+    Biocompat(#[from] BiocompatViolation),
     #[error("MPC solver error: {0}")]
-    Solver(String),
+    Solver(#[from] MpcSolveError),
+    #[error("MPC solver error: solver returned an empty control sequence")]
+    EmptyPlan,
+    #[error("decision log write failed: {0}")]
+    Audit(#[source] AuditError),
+}
+
+fn joined(violations: &[CorridorViolation]) -> String {
+    violations.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ")
+}
+
+/// Stable, machine-readable class of an [`MpcRuntimeError`] or a
+/// [`FleetError`](crate::fleet::FleetError), for picking a fallback per class
+/// and counting violations by type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ViolationCode {
+    CorridorHardBand,
+    LyapunovIncrease,
+    BioRiskTooHigh,
+    BioEcoTooLow,
+    BioDistressCoupling,
+    SolverInvalidConfig,
+    SolverInfeasible,
+    SolverInternal,
+    SolverTimeout,
+    SolverEmptyPlan,
+    AuditWrite,
+    FleetLyapunovIncrease,
+    FleetUnknownNode,
+    FleetGateTable,
+}
+
+impl ViolationCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ViolationCode::CorridorHardBand => "CORRIDOR_HARD_BAND",
+            ViolationCode::LyapunovIncrease => "LYAPUNOV_INCREASE",
+            ViolationCode::BioRiskTooHigh => "BIO_RISK_TOO_HIGH",
+            ViolationCode::BioEcoTooLow => "BIO_ECO_TOO_LOW",
+            ViolationCode::BioDistressCoupling => "BIO_DISTRESS_COUPLING",
+            ViolationCode::SolverInvalidConfig => "SOLVER_INVALID_CONFIG",
+            ViolationCode::SolverInfeasible => "SOLVER_INFEASIBLE",
+            ViolationCode::SolverInternal => "SOLVER_INTERNAL",
+            ViolationCode::SolverTimeout => "SOLVER_TIMEOUT",
+            ViolationCode::SolverEmptyPlan => "SOLVER_EMPTY_PLAN",
+            ViolationCode::AuditWrite => "AUDIT_WRITE",
+            ViolationCode::FleetLyapunovIncrease => "FLEET_LYAPUNOV_INCREASE",
+            ViolationCode::FleetUnknownNode => "FLEET_UNKNOWN_NODE",
+            ViolationCode::FleetGateTable => "FLEET_GATE_TABLE",
+        }
+    }
+}
+
+impl fmt::Display for ViolationCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl MpcRuntimeError {
+    pub fn code(&self) -> ViolationCode {
+        match self {
+            MpcRuntimeError::Corridor { .. } => ViolationCode::CorridorHardBand,
+            MpcRuntimeError::Lyapunov { .. } => ViolationCode::LyapunovIncrease,
+            MpcRuntimeError::Biocompat(BiocompatViolation::RiskTooHigh(_)) => ViolationCode::BioRiskTooHigh,
+            MpcRuntimeError::Biocompat(BiocompatViolation::EcoImpactTooLow(_)) => ViolationCode::BioEcoTooLow,
+            MpcRuntimeError::Biocompat(BiocompatViolation::DistressCoupling) => ViolationCode::BioDistressCoupling,
+            MpcRuntimeError::Solver(MpcSolveError::InvalidConfig) => ViolationCode::SolverInvalidConfig,
+            MpcRuntimeError::Solver(MpcSolveError::Infeasible) => ViolationCode::SolverInfeasible,
+            MpcRuntimeError::Solver(MpcSolveError::Internal) => ViolationCode::SolverInternal,
+            MpcRuntimeError::Solver(MpcSolveError::Timeout) => ViolationCode::SolverTimeout,
+            MpcRuntimeError::EmptyPlan => ViolationCode::SolverEmptyPlan,
+            MpcRuntimeError::Audit(_) => ViolationCode::AuditWrite,
+        }
+    }
 }
 
 /// Main runtime that enforces corridors, Lyapunov stability, and bioscale compatibility.[file:39][file:92]
-pub struct MpcRuntime<S: MpcSolver> {
+///
+/// `D` predicts the state along the planned trajectory and `R` turns each
+/// predicted state into `V`; the defaults use `V_t = Σ w_j r_j` and hold the
+/// state, which predicts nothing, so the Lyapunov check is skipped, and
+/// recorded as skipped rather than passed, until a real model is set with
+/// [`MpcRuntime::with_prediction`].
+pub struct MpcRuntime<S: MpcSolver, D: DynamicsModel = HoldDynamics, R: ResidualComputer = WeightedSumResidual> {
     cfg: MpcRuntimeConfig,
     solver: S,
     corridor_check: CorridorCheck,
     lyap_check: LyapunovResidualChecker,
     bio_guard: BiocompatGuard,
+    dynamics: D,
+    resid: R,
+    /// Last move that passed every check, for `SolverFallback::HoldLastSafe`.
+    last_safe: Mutex<Option<MpcControlSlice>>,
+    solve_times: Mutex<SolveTimes>,
+    /// Cleared by [`MpcRuntime::offline`].
+    wall_clock: bool,
 }
 
 impl<S: MpcSolver> MpcRuntime<S> {
@@ -44,9 +320,64 @@ impl<S: MpcSolver> MpcRuntime<S> {
         lyap_check: LyapunovResidualChecker,
         bio_guard: BiocompatGuard,
     ) -> Self {
-        Self { cfg, solver, corridor_check, lyap_check, bio_guard }
+        Self {
+            cfg,
+            solver,
+            corridor_check,
+            lyap_check,
+            bio_guard,
+            dynamics: HoldDynamics,
+            resid: WeightedSumResidual,
+            last_safe: Mutex::new(None),
+            solve_times: Mutex::new(SolveTimes::default()),
+            wall_clock: true,
+        }
+    }
+}
+
+impl<S: MpcSolver, D: DynamicsModel, R: ResidualComputer> MpcRuntime<S, D, R> {
+    /// Replace the plant model and residual used for the Lyapunov check.
+    pub fn with_prediction<D2: DynamicsModel, R2: ResidualComputer>(
+        self,
+        dynamics: D2,
+        resid: R2,
+    ) -> MpcRuntime<S, D2, R2> {
+        MpcRuntime {
+            cfg: self.cfg,
+            solver: self.solver,
+            corridor_check: self.corridor_check,
+            lyap_check: self.lyap_check,
+            bio_guard: self.bio_guard,
+            dynamics,
+            resid,
+            last_safe: self.last_safe,
+            solve_times: self.solve_times,
+            wall_clock: self.wall_clock,
+        }
+    }
+
+    /// This runtime with no step history and no solve deadline, for runs that
+    /// must be reproducible rather than real-time (replay, simulation).
+    pub(crate) fn offline(self) -> Self {
+        Self {
+            last_safe: Mutex::new(None),
+            solve_times: Mutex::new(SolveTimes::default()),
+            wall_clock: false,
+            ..self
+        }
     }
 
+    /// Wall-clock times of the solves run so far, including failed ones.
+    pub fn solve_stats(&self) -> SolveStats {
+        self.solve_times.lock().unwrap_or_else(|p| p.into_inner()).stats()
+    }
+
+    /// Checker for callers that validate the fleet residual after applying a command.
+    pub fn lyapunov_checker(&self) -> &LyapunovResidualChecker {
+        &self.lyap_check
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn step(
         &self,
         shard: &InfraNodeShardSnapshot,
@@ -56,31 +387,507 @@ impl<S: MpcSolver> MpcRuntime<S> {
         neuro: Option<&NeuroRightsSnapshot>,
         bio_profile: Option<&BioIntegrationProfile>,
         obj: &BiocompatObjective,
-    ) -> Result<InfraControlCommand, MpcRuntimeError> {
-        // 1. Local corridors.[file:39]
-        self.corridor_check
-            .check_snapshot(shard)
-            .map_err(|e| MpcRuntimeError::Corridor(e.to_string()))?;
+    ) -> Result<StepOutcome, MpcRuntimeError> {
+        self.step_traced(shard, ker, eco, risk, neuro, bio_profile, obj, &mut StepTrace::default())
+    }
+
+    /// `step`, recording every check and the solved control sequence in `trace`.
+    ///
+    /// The measured `ker`, `eco` and `risk` only go into the decision log; the
+    /// solver scores its own KER predictions, so an envelope or cognitive-load
+    /// breach surfaces as an infeasible solve.
+    #[allow(clippy::too_many_arguments)]
+    pub fn step_traced(
+        &self,
+        shard: &InfraNodeShardSnapshot,
+        _ker: &KerVector,
+        _eco: EcoImpactScalar,
+        _risk: RiskScalar,
+        neuro: Option<&NeuroRightsSnapshot>,
+        bio_profile: Option<&BioIntegrationProfile>,
+        obj: &BiocompatObjective,
+        trace: &mut StepTrace,
+    ) -> Result<StepOutcome, MpcRuntimeError> {
+        // 1. Local corridors: the hard band stops, the soft band derates the move (E.3).[file:39]
+        let derate = match self.corridor_check.decide(shard) {
+            CorridorDecision::Stop { violations } => {
+                let err = MpcRuntimeError::Corridor { violations };
+                trace.failed("corridor", format!("{err}; stopping"));
+                let stop = vec![0.0; shard.controls.len()];
+                return Ok(StepOutcome {
+                    command: shard.control_from_mpc(&stop),
+                    fallback: Some(FallbackActivation { action: FallbackAction::Stop, trigger: err.code(), reason: err.to_string() }),
+                    timed_out: false,
+                });
+            }
+            CorridorDecision::Derate { factor, channels } => {
+                trace.passed("corridor", Some(format!("derate factor={factor} channels={channels:?}")));
+                Some(factor)
+            }
+            CorridorDecision::Ok => {
+                trace.passed("corridor", None);
+                None
+            }
+        };
 
         // 2. Optional bioscale check (if the node is organically integrated).[file:92]
         if let (Some(n), Some(p)) = (neuro, bio_profile) {
-            self.bio_guard
-                .check_neurorights(n, p)
-                .map_err(|e| MpcRuntimeError::Biocompat(e.to_string()))?;
+            let checked = self.bio_guard.check_neurorights(n, p).map_err(MpcRuntimeError::from);
+            trace.outcome("bioscale", checked)?;
         }
 
         // 3. Extract MPC state and solve with biocompatibility objective.[file:39]
         let extractor = shard.mpc_state_extractor();
         let x0: MpcStateSlice = extractor.extract_state(shard);
+        let hint = extractor.extract_control_hint(shard);
+
+        // Penalize moves away from the actuators' current setpoints.
+        let obj = obj.with_previous_control(hint.clone());
+        // Past its deadline the solver returns its best iterate; a bad budget
+        // (negative or NaN) counts as zero, an overlong one as no deadline.
+        let started = Instant::now();
+        let budget = self.cfg.solve_budget_seconds.unwrap_or(self.cfg.horizon.dt_seconds).max(0.0);
+        let deadline = Duration::try_from_secs_f64(budget)
+            .ok()
+            .and_then(|d| started.checked_add(d))
+            .filter(|_| self.wall_clock);
+        let solved = self.solver.solve(&self.cfg.horizon, &obj, &x0, deadline);
+        self.solve_times
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .record(started.elapsed(), solved.as_ref().map_or_else(|e| matches!(e, MpcSolveError::Timeout), |s| s.timed_out));
+        let solution = match solved.map_err(MpcRuntimeError::from) {
+            Ok(s) if s.controls.is_empty() => Err(MpcRuntimeError::EmptyPlan),
+            other => other,
+        };
+        let (controls, timed_out) = match solution {
+            Ok(s) => {
+                trace.passed("solver", s.timed_out.then(|| format!("timed out after {} iterations", s.iterations)));
+                (s.controls, s.timed_out)
+            }
+            Err(e) => {
+                trace.failed("solver", e.to_string());
+                return self.solver_fallback(shard, &hint, derate, e, trace);
+            }
+        };
+        trace.controls = controls.iter().map(|c| c.u.clone()).collect();
+
+        let mut plan = controls;
+        if let Some(factor) = derate {
+            self.cfg.derate.apply(&mut plan[0], factor);
+        }
+
+        // 4. Predict the residual along the plan as applied; V must not rise at any step (E.2).[file:39][file:69]
+        if !self.dynamics.predicts_motion() {
+            trace.skipped("lyapunov", "dynamics model predicts no motion".into());
+        } else if let Err(e) = self.check_plan(shard, &x0, &plan) {
+            return match self.cfg.lyapunov_fallback {
+                LyapunovFallback::Reject => trace.outcome("lyapunov", Err(e)),
+                LyapunovFallback::Hold => {
+                    // The held setpoints are derated like any other command, and
+                    // holding is only a fallback if it keeps V from rising too.
+                    let mut held = hint.clone();
+                    if let Some(factor) = derate {
+                        self.cfg.derate.apply(&mut held, factor);
+                    }
+                    if let Err(held_err) = self.check_plan(shard, &x0, std::slice::from_ref(&held)) {
+                        trace.failed("lyapunov", format!("{e}; holding current setpoints also fails"));
+                        return Err(held_err);
+                    }
+                    trace.failed("lyapunov", format!("{e}; holding current setpoints"));
+                    Ok(StepOutcome {
+                        command: shard.control_from_mpc(&held),
+                        fallback: Some(FallbackActivation {
+                            action: FallbackAction::HoldCurrent,
+                            trigger: e.code(),
+                            reason: e.to_string(),
+                        }),
+                        timed_out,
+                    })
+                }
+            };
+        } else {
+            trace.passed("lyapunov", None);
+        }
+
+        let u0 = plan.swap_remove(0);
+        let command = shard.control_from_mpc(&u0);
+        *self.last_safe.lock().unwrap_or_else(|p| p.into_inner()) = Some(u0);
+
+        Ok(StepOutcome { command, fallback: None, timed_out })
+    }
+
+    /// Apply `cfg.solver_fallback` to a failed solve, derated by the corridor's
+    /// `derate` factor, or return `err` under `Reject`.
+    fn solver_fallback(
+        &self,
+        shard: &InfraNodeShardSnapshot,
+        current: &MpcControlSlice,
+        derate: Option<f64>,
+        err: MpcRuntimeError,
+        trace: &mut StepTrace,
+    ) -> Result<StepOutcome, MpcRuntimeError> {
+        let (action, u) = match &self.cfg.solver_fallback {
+            SolverFallback::Reject => return Err(err),
+            SolverFallback::HoldLastSafe => {
+                let last = self.last_safe.lock().unwrap_or_else(|p| p.into_inner());
+                let u = last
+                    .as_ref()
+                    .filter(|u| u.node_id == shard.node_id && u.u.len() == current.u.len())
+                    .map_or_else(|| current.u.clone(), |u| u.u.clone());
+                (FallbackAction::HoldLastSafe, u)
+            }
+            SolverFallback::RampToSafe(ramp) => (FallbackAction::RampToSafe, ramp.next(&current.u)),
+            SolverFallback::ShardHint => {
+                (FallbackAction::ShardHint, shard.mpc_state_extractor().extract_control_hint(shard).u)
+            }
+        };
+        let mut u = MpcControlSlice { node_id: current.node_id.clone(), u };
+        match derate {
+            Some(factor) => {
+                self.cfg.derate.apply(&mut u, factor);
+                trace.passed("fallback", Some(format!("{action:?}, derate factor={factor}")));
+            }
+            None => trace.passed("fallback", Some(format!("{action:?}"))),
+        }
+        Ok(StepOutcome {
+            command: shard.control_from_mpc(&u),
+            timed_out: err.code() == ViolationCode::SolverTimeout,
+            fallback: Some(FallbackActivation { action, trigger: err.code(), reason: err.to_string() }),
+        })
+    }
+
+    /// Roll `plan` through the dynamics from `x0` and run `check_step` on each
+    /// consecutive pair of residuals, starting from the measured one.
+    fn check_plan(
+        &self,
+        shard: &InfraNodeShardSnapshot,
+        x0: &MpcStateSlice,
+        plan: &[MpcControlSlice],
+    ) -> Result<(), MpcRuntimeError> {
+        let mut x = x0.clone();
+        let mut before = shard.residual();
+        for (i, u) in plan.iter().enumerate() {
+            x = self.dynamics.step(&x, u, self.cfg.horizon.dt_seconds);
+            let after = predicted_residual(shard, &x);
+            self.lyap_check
+                .check_step(&self.resid, &before, &after)
+                .map_err(|source| MpcRuntimeError::Lyapunov { step: i + 1, source })?;
+            before = after;
+        }
+        Ok(())
+    }
+
+    /// Residual `shard` is predicted to reach one step after applying `cmd`.
+    ///
+    /// Setpoints are matched to actuators by name; actuators without one keep
+    /// their current setting.
+    pub fn predict_residual(&self, shard: &InfraNodeShardSnapshot, cmd: &InfraControlCommand) -> GlobalResidual {
+        let x0 = shard.mpc_state_extractor().extract_state(shard);
+        let u = command_controls(shard, cmd);
+        predicted_residual(shard, &self.dynamics.step(&x0, &u, self.cfg.horizon.dt_seconds))
+    }
+
+    /// Re-run the corridor and Lyapunov checks on a command changed after
+    /// `step`, e.g. scaled down to a flow limit shared with other nodes.
+    ///
+    /// A `Clamp` derate is re-applied to `cmd`; scaling a move down already keeps
+    /// it within a `Scale` one. Only the applied move is checked, since the rest
+    /// of the node's plan no longer leads on from it.
+    pub fn recheck_command(&self, shard: &InfraNodeShardSnapshot, cmd: &mut InfraControlCommand) -> Result<(), MpcRuntimeError> {
+        let mut u = command_controls(shard, cmd);
+        match self.corridor_check.decide(shard) {
+            CorridorDecision::Stop { violations } => {
+                return Err(MpcRuntimeError::Corridor { violations });
+            }
+            CorridorDecision::Derate { factor, .. } if self.cfg.derate == DerateMode::Clamp => {
+                self.cfg.derate.apply(&mut u, factor);
+                *cmd = shard.control_from_mpc(&u);
+            }
+            _ => {}
+        }
+        if !self.dynamics.predicts_motion() {
+            return Ok(());
+        }
+        let x0 = shard.mpc_state_extractor().extract_state(shard);
+        self.check_plan(shard, &x0, std::slice::from_ref(&u))
+    }
+
+    pub fn config(&self) -> &MpcRuntimeConfig {
+        &self.cfg
+    }
+}
+
+/// Normalized controls `cmd` sets on `shard`, matched to actuators by name;
+/// actuators without a setpoint keep their current setting.
+fn command_controls(shard: &InfraNodeShardSnapshot, cmd: &InfraControlCommand) -> MpcControlSlice {
+    let u = shard
+        .controls
+        .iter()
+        .map(|c| {
+            let current = cmd.setpoints.iter().find(|sp| sp.name == c.name).map_or(c.current, |sp| sp.value);
+            ControlChannel { current, ..c.clone() }.normalized()
+        })
+        .collect();
+    MpcControlSlice { node_id: shard.node_id.clone(), u }
+}
+
+/// Residual of `shard` with each channel's risk replaced by the predicted state.
+///
+/// `x` is in channel order, as produced by `ShardStateExtractor`; channels it
+/// does not cover keep their measured risk.
+fn predicted_residual(shard: &InfraNodeShardSnapshot, x: &MpcStateSlice) -> GlobalResidual {
+    let mut resid = shard.residual();
+    for (t, &r) in resid.terms.iter_mut().zip(&x.x) {
+        t.r = r;
+        t.within_corridor = r.is_finite() && r <= 1.0;
+    }
+    resid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mpc_kernel::{
+        AffineKerModel, BiocompatObjectiveConfig, LinearStateSpace, ObjectiveTermWeights, MpcSolution,
+        ProjectedGradientSolver,
+    };
+    use crate::test_support::{self, basin, config, exergy_model, runtime_with, sla_model};
+
+    /// Pumping costs exergy and does nothing else, so the solver idles.
+    fn runtime(fallback: LyapunovFallback) -> MpcRuntime<ProjectedGradientSolver<AffineKerModel>, LinearStateSpace> {
+        test_support::runtime(MpcRuntimeConfig { lyapunov_fallback: fallback, ..config(3) }, exergy_model())
+            // Inflow raises the basin 0.02/s; full pump speed drains 0.05/s.
+            .with_prediction(
+                LinearStateSpace::new(vec![vec![0.0]], vec![vec![-0.05]], vec![0.02]).unwrap(),
+                WeightedSumResidual,
+            )
+    }
+
+    fn objective() -> BiocompatObjective {
+        test_support::objective(ObjectiveTermWeights { lambda_energy: 1.0, ..Default::default() })
+    }
+
+    fn step(rt: &MpcRuntime<ProjectedGradientSolver<AffineKerModel>, LinearStateSpace>) -> Result<StepOutcome, MpcRuntimeError> {
+        let snap = basin(5.0);
+        rt.step(&snap, &KerVector::default(), 0.6, 0.5, None, None, &objective())
+    }
+
+    #[test]
+    fn test_rising_residual_is_rejected_or_held() {
+        // The cheapest plan lets the basin fill, so V rises after the first move.
+        let err = step(&runtime(LyapunovFallback::Reject)).unwrap_err();
+        assert!(
+            matches!(&err, MpcRuntimeError::Lyapunov { step: 1, source: LyapunovViolation::Increased { v_t, v_t1 } } if v_t1 > v_t),
+            "{err}"
+        );
+        assert_eq!(err.code(), ViolationCode::LyapunovIncrease);
+        assert!(err.to_string().contains("predicted step 1"));
+
+        // Holding the pump at 30 % drains less than the inflow, so holding fails too.
+        let err = step(&runtime(LyapunovFallback::Hold)).unwrap_err();
+        assert!(matches!(err, MpcRuntimeError::Lyapunov { step: 1, .. }), "{err}");
+
+        // At 60 % the basin drains, so holding is a valid fallback.
+        let mut snap = basin(5.0);
+        snap.controls[0].current = 60.0;
+        let held = runtime(LyapunovFallback::Hold)
+            .step(&snap, &KerVector::default(), 0.6, 0.5, None, None, &objective())
+            .unwrap();
+        assert_eq!(held.command.setpoints[0].value, 60.0);
+        assert_eq!(held.fallback.map(|f| (f.action, f.trigger)), Some((FallbackAction::HoldCurrent, ViolationCode::LyapunovIncrease)));
+
+        // In the soft band (5 m between gold 4 and legal 8) the held 80 % is derated to 60 %.
+        snap.controls[0].current = 80.0;
+        snap.channels[0].band.gold_limit = Some(4.0);
+        snap.channels[0].band.legal_limit = Some(8.0);
+        let held = runtime(LyapunovFallback::Hold)
+            .step(&snap, &KerVector::default(), 0.6, 0.5, None, None, &objective())
+            .unwrap();
+        assert!((held.command.setpoints[0].value - 60.0).abs() < 1e-9, "{held:?}");
+        assert_eq!(held.fallback.unwrap().action, FallbackAction::HoldCurrent);
+    }
+
+    #[test]
+    fn test_rise_later_in_horizon_is_caught() {
+        // Full pump speed drains the basin; idling afterwards lets it refill.
+        let rt = runtime(LyapunovFallback::Reject);
+        let snap = basin(5.0);
+        let x0 = snap.mpc_state_extractor().extract_state(&snap);
+        let u = |u: f64| MpcControlSlice { node_id: snap.node_id.clone(), u: vec![u] };
+        assert!(rt.check_plan(&snap, &x0, &[u(1.0), u(1.0)]).is_ok());
+        let err = rt.check_plan(&snap, &x0, &[u(1.0), u(0.0)]).unwrap_err();
+        assert!(matches!(err, MpcRuntimeError::Lyapunov { step: 2, .. }), "{err}");
+    }
+
+    /// Plans a constant move until told to fail.
+    struct FlakySolver {
+        u: f64,
+        fail: std::cell::Cell<bool>,
+    }
+
+    impl MpcSolver for FlakySolver {
+        fn solve(
+            &self,
+            cfg: &MpcHorizonConfig,
+            _: &BiocompatObjective,
+            x0: &MpcStateSlice,
+            deadline: Option<Instant>,
+        ) -> Result<MpcSolution, MpcSolveError> {
+            if self.fail.get() {
+                return Err(MpcSolveError::Infeasible);
+            }
+            Ok(MpcSolution {
+                controls: vec![MpcControlSlice { node_id: x0.node_id.clone(), u: vec![self.u] }; cfg.horizon_steps],
+                iterations: 1,
+                timed_out: deadline.is_some_and(|d| Instant::now() >= d),
+            })
+        }
+    }
+
+    #[test]
+    fn test_solve_deadline_and_stats() {
+        let mut cfg = runtime(LyapunovFallback::Reject).cfg.clone();
+        cfg.solve_budget_seconds = Some(0.0);
+        let rt = runtime_with(cfg, FlakySolver { u: 0.1, fail: std::cell::Cell::new(false) });
+        let mut trace = StepTrace::default();
+        let out = rt
+            .step_traced(&basin(5.0), &KerVector::default(), 0.6, 0.5, None, None, &objective(), &mut trace)
+            .unwrap();
+        // The best iterate is still applied, and the overrun is reported.
+        assert!(out.timed_out && out.fallback.is_none());
+        assert!((out.command.setpoints[0].value - 10.0).abs() < 1e-9);
+        let solver = trace.checks.iter().find(|c| c.check == "solver").unwrap();
+        assert!(solver.passed && solver.detail.as_deref() == Some("timed out after 1 iterations"));
+
+        rt.solver.fail.set(true);
+        assert!(rt.step(&basin(5.0), &KerVector::default(), 0.6, 0.5, None, None, &objective()).is_err());
+        let stats = rt.solve_stats();
+        assert_eq!((stats.solves, stats.timed_out), (2, 1));
+        assert!(stats.p50_seconds <= stats.p99_seconds && stats.p99_seconds <= stats.max_seconds);
+    }
+
+    #[test]
+    fn test_solver_failure_fallbacks() {
+        let base = runtime(LyapunovFallback::Reject);
+        let run = |fallback: SolverFallback| {
+            let rt = runtime_with(
+                MpcRuntimeConfig { solver_fallback: fallback, ..base.cfg.clone() },
+                FlakySolver { u: 0.1, fail: std::cell::Cell::new(false) },
+            );
+            let snap = basin(5.0);
+            let step = |rt: &MpcRuntime<FlakySolver>| rt.step(&snap, &KerVector::default(), 0.6, 0.5, None, None, &objective());
+            assert!(step(&rt).unwrap().fallback.is_none());
+            rt.solver.fail.set(true);
+            step(&rt)
+        };
+
+        let err = run(SolverFallback::Reject).unwrap_err();
+        assert_eq!(err.code(), ViolationCode::SolverInfeasible);
+
+        // Last safe move was 10 %; current setpoint is 30 %.
+        let held = run(SolverFallback::HoldLastSafe).unwrap();
+        assert!((held.command.setpoints[0].value - 10.0).abs() < 1e-9);
+        let f = held.fallback.unwrap();
+        assert_eq!((f.action, f.trigger), (FallbackAction::HoldLastSafe, ViolationCode::SolverInfeasible));
+
+        let ramp = run(SolverFallback::RampToSafe(SafeStateRamp::new(vec![0.0], 0.05).unwrap())).unwrap();
+        assert!((ramp.command.setpoints[0].value - 25.0).abs() < 1e-9);
+        assert_eq!(ramp.fallback.unwrap().action, FallbackAction::RampToSafe);
+
+        let hint = run(SolverFallback::ShardHint).unwrap();
+        assert_eq!(hint.command.setpoints[0].value, 30.0);
+        assert_eq!(hint.fallback.unwrap().action, FallbackAction::ShardHint);
+
+        // The soft-band derate applies to fallbacks too: 5 m between gold 4 and legal 8 scales by 0.75.
+        let rt = runtime_with(
+            MpcRuntimeConfig { solver_fallback: SolverFallback::ShardHint, ..base.cfg.clone() },
+            FlakySolver { u: 0.1, fail: std::cell::Cell::new(true) },
+        );
+        let mut snap = basin(5.0);
+        snap.channels[0].band.gold_limit = Some(4.0);
+        snap.channels[0].band.legal_limit = Some(8.0);
+        let derated = rt.step(&snap, &KerVector::default(), 0.6, 0.5, None, None, &objective()).unwrap();
+        assert!((derated.command.setpoints[0].value - 22.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_safe_state_ramp_is_validated() {
+        assert_eq!(SafeStateRamp::new(vec![0.0], 0.0), Err(SafeStateRampError::InvalidMaxDelta(0.0)));
+        assert!(matches!(SafeStateRamp::new(vec![0.0], f64::NAN), Err(SafeStateRampError::InvalidMaxDelta(_))));
+        assert_eq!(
+            SafeStateRamp::new(vec![0.0, 1.5], 0.1),
+            Err(SafeStateRampError::InvalidSafeState { index: 1, value: 1.5 })
+        );
+
+        let parse = |body: &str| toml::from_str::<SolverFallback>(&format!("policy = \"ramp_to_safe\"\n{body}"));
+        let ramp = parse("safe_state = [0.0, 1.0]\nmax_delta = 0.1").unwrap();
+        assert_eq!(ramp, SolverFallback::RampToSafe(SafeStateRamp::new(vec![0.0, 1.0], 0.1).unwrap()));
+        let err = parse("safe_state = [0.0]\nmax_delta = -0.1").unwrap_err();
+        assert!(err.to_string().contains("max_delta must be finite and positive"), "{err}");
+    }
+
+    #[test]
+    fn test_errors_keep_violation_payloads() {
+        let rt = runtime(LyapunovFallback::Reject);
+        let neuro = NeuroRightsSnapshot { normalized_risk: 0.7, eco_impact_index: 0.5 };
+        let err = rt
+            .step(&basin(5.0), &KerVector::default(), 0.6, 0.5, Some(&neuro), Some(&BioIntegrationProfile::default()), &objective())
+            .unwrap_err();
+        assert!(matches!(err, MpcRuntimeError::Biocompat(BiocompatViolation::RiskTooHigh(r)) if r == 0.7));
+        assert_eq!(err.code(), ViolationCode::BioRiskTooHigh);
+
+        // An eco floor above every predicted eco impact leaves no finite-cost plan.
+        let strict = BiocompatObjective::new_checked(BiocompatObjectiveConfig { e_min: 0.7, ..objective().config().clone() }).unwrap();
+        let err = rt.step(&basin(5.0), &KerVector::default(), 0.6, 0.5, None, None, &strict).unwrap_err();
+        assert!(matches!(err, MpcRuntimeError::Solver(MpcSolveError::Infeasible)));
+        assert_eq!(err.code().as_str(), "SOLVER_INFEASIBLE");
+        assert_eq!(serde_json::to_string(&err.code()).unwrap(), "\"SOLVER_INFEASIBLE\"");
+    }
+
+    #[test]
+    fn test_hold_dynamics_skip_lyapunov_check() {
+        // Hold dynamics predict nothing, so even a strict checker is not consulted
+        // and the trace says so rather than reporting a pass.
+        let mut rt = runtime(LyapunovFallback::Reject).with_prediction(HoldDynamics, WeightedSumResidual);
+        rt.lyap_check.allow_equal = false;
+        let mut trace = StepTrace::default();
+        let cmd = rt
+            .step_traced(&basin(5.0), &KerVector::default(), 0.6, 0.5, None, None, &objective(), &mut trace)
+            .unwrap();
+        assert!(cmd.command.setpoints[0].value < 1.0);
+        let lyap = trace.checks.iter().find(|c| c.check == "lyapunov").unwrap();
+        assert!(!lyap.passed && lyap.skipped, "{lyap:?}");
+    }
+
+    #[test]
+    fn test_soft_band_derates_applied_move() {
+        // SLA misses fall with pump speed, so the unconstrained optimum is full speed.
+        let obj = test_support::objective(ObjectiveTermWeights { lambda_slaviolation: 1.0, ..Default::default() });
+        let mut rt = test_support::runtime(config(2), sla_model(1.0));
+        let mut snap = basin(5.0);
+        snap.channels[0].band.gold_limit = Some(4.0);
+        snap.channels[0].band.legal_limit = Some(8.0);
+
+        // 5 m is a quarter of the way from gold (4) to legal (8): factor 0.75.
+        let step = |rt: &MpcRuntime<_>, snap: &InfraNodeShardSnapshot| rt.step(snap, &KerVector::default(), 0.6, 0.5, None, None, &obj);
+        assert!((step(&rt, &snap).unwrap().command.setpoints[0].value - 75.0).abs() < 1e-6);
 
-        let controls = self.solver
-            .solve(&self.cfg.horizon, obj, &x0)
-            .map_err(|e| MpcRuntimeError::Solver(format!("{e}")))?;
+        rt.cfg.derate = DerateMode::Clamp;
+        snap.channels[0].value = 7.0;
+        assert!((step(&rt, &snap).unwrap().command.setpoints[0].value - 25.0).abs() < 1e-6);
 
-        // 4. Build next-state snapshot candidate and check Lyapunov residual externally.
-        //    (Assumes contracts_core provides residual computation over the full fleet.)[file:39][file:69]
-        let command = shard.control_from_mpc(&controls[0]);
+        // At the legal limit the derate factor is 0: the pump is cut to zero, but nothing has been breached.
+        snap.channels[0].value = 8.0;
+        let cut = step(&rt, &snap).unwrap();
+        assert_eq!((cut.command.setpoints[0].value, cut.fallback), (0.0, None));
 
-        Ok(command)
+        // Past the legal limit the pump is stopped and the stop is reported.
+        snap.channels[0].value = 8.5;
+        let stopped = step(&rt, &snap).unwrap();
+        assert_eq!(stopped.command.setpoints[0].value, 0.0);
+        let f = stopped.fallback.unwrap();
+        assert_eq!((f.action, f.trigger), (FallbackAction::Stop, ViolationCode::CorridorHardBand));
+        assert_eq!(f.reason, "corridor violation: channel 0 (basin_level): 8.5 breaches legal limit 8");
     }
 }
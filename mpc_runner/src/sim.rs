@@ -0,0 +1,276 @@
+//! Receding-horizon closed-loop simulation: solve, apply the first move to a
+//! plant model, advance the snapshot, repeat. Scenarios are TOML files so a
+//! controller tuning can be replayed offline and diffed as a CSV trace.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use contracts_core::infra::{InfraControlCommand, InfraNodeShardSnapshot};
+use contracts_core::lyapunov::{ResidualComputer, WeightedSumResidual};
+use mpc_constraints::bioscale_guard::BiocompatGuardConfig;
+use mpc_constraints::{BiocompatGuard, CorridorCheck, LyapunovResidualChecker};
+use mpc_kernel::{
+    AffineKerModel, BiocompatObjective, BiocompatObjectiveConfig, DynamicsModel, KerModel, LinearStateSpace,
+    ProjectedGradientConfig, ProjectedGradientSolver, StateExtractor,
+};
+use crate::runner::{MpcRuntime, MpcRuntimeConfig};
+
+#[derive(Debug, thiserror::Error)]
+pub enum SimError {
+    #[error("io error on {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("malformed scenario {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+    #[error("invalid scenario: {0}")]
+    Invalid(String),
+}
+
+/// Applies a command to the physical node and reports where it ends up after `dt`.
+pub trait PlantModel {
+    fn apply(&self, snap: &InfraNodeShardSnapshot, cmd: &InfraControlCommand, dt: f64) -> InfraNodeShardSnapshot;
+}
+
+/// Plant driven by a [`DynamicsModel`] on the snapshot's normalized risk coordinates.
+///
+/// Setpoints are matched to actuators by name and clamped to their range. Each
+/// predicted risk is mapped back to a channel reading with
+/// `CorridorBand::value_at_risk`, so readings stay within `[r_min, r_max]`. The
+/// timestamp is left as is; the trace carries simulated time.
+#[derive(Clone, Debug)]
+pub struct StateSpacePlant<D: DynamicsModel>(pub D);
+
+impl<D: DynamicsModel> PlantModel for StateSpacePlant<D> {
+    fn apply(&self, snap: &InfraNodeShardSnapshot, cmd: &InfraControlCommand, dt: f64) -> InfraNodeShardSnapshot {
+        let mut next = snap.clone();
+        for c in &mut next.controls {
+            if let Some(sp) = cmd.setpoints.iter().find(|sp| sp.name == c.name) {
+                c.current = sp.value.clamp(c.min, c.max);
+            }
+        }
+        let extractor = snap.mpc_state_extractor();
+        let x = extractor.extract_state(snap);
+        let u = extractor.extract_control_hint(&next);
+        let x1 = self.0.step(&x, &u, dt);
+        for (ch, &r) in next.channels.iter_mut().zip(&x1.x) {
+            ch.value = ch.band.value_at_risk(r);
+        }
+        next
+    }
+}
+
+/// One offline closed-loop run.
+///
+/// `plant` names a [`LinearStateSpace`] file, resolved relative to the scenario.
+/// The controller predicts with `prediction` when given (to study model
+/// mismatch) and with the plant model otherwise.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Scenario {
+    pub steps: usize,
+    pub runtime: MpcRuntimeConfig,
+    pub lyapunov: LyapunovResidualChecker,
+    pub guard: BiocompatGuardConfig,
+    pub objective: BiocompatObjectiveConfig,
+    #[serde(default)]
+    pub solver: ProjectedGradientConfig,
+    pub ker_model: AffineKerModel,
+    pub plant: PathBuf,
+    #[serde(default)]
+    pub prediction: Option<PathBuf>,
+    pub initial: InfraNodeShardSnapshot,
+}
+
+impl Scenario {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SimError> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path).map_err(|source| SimError::Io { path: path.to_path_buf(), source })?;
+        let mut scenario: Scenario =
+            toml::from_str(&data).map_err(|source| SimError::Parse { path: path.to_path_buf(), source })?;
+        let dir = path.parent().unwrap_or(Path::new("."));
+        scenario.plant = dir.join(&scenario.plant);
+        scenario.prediction = scenario.prediction.map(|p| dir.join(p));
+        Ok(scenario)
+    }
+}
+
+/// Per-step record of a closed-loop run; `v_t`, `eco` and `risk` are taken before the move.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TraceRow {
+    pub step: usize,
+    pub t_seconds: f64,
+    pub v_t: f64,
+    pub eco: f64,
+    pub risk: f64,
+    /// Normalized control applied during the step.
+    pub control: Vec<f64>,
+    /// Runtime error that forced a hold, or the fallback that replaced the plan.
+    pub violation: Option<String>,
+}
+
+fn load_model(path: &Path) -> Result<LinearStateSpace, SimError> {
+    LinearStateSpace::load(path).map_err(|e| SimError::Invalid(format!("{}: {e}", path.display())))
+}
+
+/// Run `scenario` for `scenario.steps` steps.
+///
+/// A step whose `MpcRuntime::step` fails is recorded with its error and the
+/// actuators held at their current setpoints, so a run always covers every step.
+/// Solves have no wall-clock deadline, so a run does not depend on host speed.
+pub fn simulate(scenario: &Scenario) -> Result<Vec<TraceRow>, SimError> {
+    let objective = BiocompatObjective::new_checked(scenario.objective.clone())
+        .map_err(|e| SimError::Invalid(format!("objective: {e}")))?;
+    let plant = StateSpacePlant(load_model(&scenario.plant)?);
+    let prediction = match &scenario.prediction {
+        Some(p) => load_model(p)?,
+        None => plant.0.clone(),
+    };
+    let runtime = MpcRuntime::new(
+        scenario.runtime.clone(),
+        ProjectedGradientSolver::with_dynamics(scenario.solver.clone(), scenario.ker_model.clone(), prediction.clone()),
+        CorridorCheck,
+        scenario.lyapunov.clone(),
+        BiocompatGuard::new(scenario.guard.clone()),
+    )
+    .with_prediction(prediction, WeightedSumResidual)
+    .offline();
+
+    let dt = scenario.runtime.horizon.dt_seconds;
+    let mut snap = scenario.initial.clone();
+    let mut rows = Vec::with_capacity(scenario.steps);
+    for step in 0..scenario.steps {
+        let extractor = snap.mpc_state_extractor();
+        let x = extractor.extract_state(&snap);
+        let hint = extractor.extract_control_hint(&snap);
+        let p = scenario.ker_model.predict(&x, &hint);
+        let v_t = WeightedSumResidual.value(&snap.residual());
+
+        let (cmd, violation) = match runtime.step(&snap, &p.ker, p.eco, p.risk, None, None, &objective) {
+            Ok(out) => (out.command, out.fallback.map(|f| f.to_string())),
+            Err(e) => (snap.control_from_mpc(&hint), Some(e.to_string())),
+        };
+        let next = plant.apply(&snap, &cmd, dt);
+        rows.push(TraceRow {
+            step,
+            t_seconds: step as f64 * dt,
+            v_t,
+            eco: p.eco,
+            risk: p.risk,
+            control: extractor.control_vector(&next),
+            violation,
+        });
+        snap = next;
+    }
+    Ok(rows)
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// CSV with header `step,t_seconds,v_t,eco,risk,control,violation`; control
+/// entries are `;`-separated.
+pub fn trace_csv(rows: &[TraceRow]) -> String {
+    let mut out = String::from("step,t_seconds,v_t,eco,risk,control,violation\n");
+    for r in rows {
+        let control: Vec<String> = r.control.iter().map(|u| format!("{u:.6}")).collect();
+        let _ = writeln!(
+            out,
+            "{},{},{:.6},{:.6},{:.6},{},{}",
+            r.step,
+            r.t_seconds,
+            r.v_t,
+            r.eco,
+            r.risk,
+            control.join(";"),
+            csv_field(r.violation.as_deref().unwrap_or(""))
+        );
+    }
+    out
+}
+
+/// Load a scenario, simulate it and write the trace CSV.
+pub fn run_scenario_file(scenario: impl AsRef<Path>, trace: impl AsRef<Path>) -> Result<Vec<TraceRow>, SimError> {
+    let rows = simulate(&Scenario::load(scenario)?)?;
+    let trace = trace.as_ref();
+    fs::write(trace, trace_csv(&rows)).map_err(|source| SimError::Io { path: trace.to_path_buf(), source })?;
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runner::{SafeStateRamp, SolverFallback};
+
+    fn scenario_path() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../models/mpc/scenarios/phoenix_mar_summer.toml")
+    }
+
+    #[test]
+    fn test_repo_scenario_drives_residual_down() {
+        let dir = tempfile::tempdir().unwrap();
+        let trace = dir.path().join("trace.csv");
+        let rows = run_scenario_file(scenario_path(), &trace).unwrap();
+        let scenario = Scenario::load(scenario_path()).unwrap();
+        assert_eq!(rows.len(), scenario.steps);
+        assert!(rows.iter().all(|r| r.violation.is_none()), "{rows:?}");
+        assert!(rows.windows(2).all(|w| w[1].v_t <= w[0].v_t + 1e-9));
+        assert!(rows.last().unwrap().v_t < rows[0].v_t);
+
+        let csv = fs::read_to_string(&trace).unwrap();
+        assert_eq!(csv.lines().count(), rows.len() + 1);
+        assert!(csv.starts_with("step,t_seconds,v_t,eco,risk,control,violation\n"));
+    }
+
+    #[test]
+    fn test_failed_steps_hold_and_are_logged() {
+        let mut scenario = Scenario::load(scenario_path()).unwrap();
+        scenario.steps = 2;
+        // Risk ceiling below every reachable risk: no plan is feasible.
+        scenario.objective.r_max = 0.01;
+        let rows = simulate(&scenario).unwrap();
+        let held = scenario.initial.mpc_state_extractor().control_vector(&scenario.initial);
+        for r in &rows {
+            assert!(r.violation.as_deref().is_some_and(|v| v.contains("no feasible solution")), "{r:?}");
+            assert_eq!(r.control, held);
+        }
+        assert!(trace_csv(&rows).lines().nth(1).unwrap().ends_with("no feasible solution within corridors"));
+        assert_eq!(csv_field("stop, hold"), "\"stop, hold\"");
+    }
+
+    #[test]
+    fn test_simulation_ignores_solve_budget() {
+        let mut scenario = Scenario::load(scenario_path()).unwrap();
+        scenario.steps = 3;
+        let unbounded = simulate(&scenario).unwrap();
+        // A zero budget would stop every solve at its seed if it applied.
+        scenario.runtime.solve_budget_seconds = Some(0.0);
+        assert_eq!(simulate(&scenario).unwrap(), unbounded);
+    }
+
+    #[test]
+    fn test_fallback_policy_commands_at_high_risk() {
+        let mut scenario = Scenario::load(scenario_path()).unwrap();
+        scenario.steps = 3;
+        // Predicted risk is above the 0.9 ceiling whatever the pump does, so every solve is infeasible.
+        scenario.ker_model.base.risk = 0.95;
+        scenario.runtime.solver_fallback = SolverFallback::RampToSafe(SafeStateRamp::new(vec![0.0], 0.1).unwrap());
+        let rows = simulate(&scenario).unwrap();
+        // The pump starts at 1200 of 3000 rpm and ramps towards off.
+        for (r, expected) in rows.iter().zip([0.3, 0.2, 0.1]) {
+            assert!(r.violation.as_deref().is_some_and(|v| v.starts_with("fallback RampToSafe")), "{r:?}");
+            assert!((r.control[0] - expected).abs() < 1e-9, "{r:?}");
+        }
+    }
+}
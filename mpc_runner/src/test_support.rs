@@ -0,0 +1,101 @@
+//! Fixtures shared by the unit tests in this crate.
+
+use contracts_core::corridor::{CorridorBand, Direction};
+use contracts_core::infra::{ControlChannel, InfraNodeShardSnapshot, ShardChannel};
+use contracts_core::metrics::KerVector;
+use mpc_constraints::bioscale_guard::BiocompatGuardConfig;
+use mpc_constraints::{BiocompatGuard, CorridorCheck, LyapunovResidualChecker};
+use mpc_kernel::solver::MpcSolver;
+use mpc_kernel::{
+    AffineKerModel, BiocompatObjective, BiocompatObjectiveConfig, KerPrediction, MpcHorizonConfig,
+    ObjectiveTermWeights, ProjectedGradientConfig, ProjectedGradientSolver,
+};
+use crate::runner::{MpcRuntime, MpcRuntimeConfig};
+
+pub(crate) type PgRuntime = MpcRuntime<ProjectedGradientSolver<AffineKerModel>>;
+
+/// A node with one channel on a 0–10 m corridor, without soft or legal limits,
+/// and one actuator.
+pub(crate) fn shard(node_id: &str, param: &str, value: f64, control: ControlChannel) -> InfraNodeShardSnapshot {
+    InfraNodeShardSnapshot {
+        node_id: node_id.into(),
+        timestamp: "2026-07-15T14:00:00Z".into(),
+        channels: vec![ShardChannel {
+            band: CorridorBand {
+                param_name: param.into(),
+                unit: "m".into(),
+                direction: Direction::Max,
+                r_min: 0.0,
+                r_max: 10.0,
+                weight_w: 1.0,
+                channel: 0,
+                legal_limit: None,
+                gold_limit: None,
+            },
+            value,
+        }],
+        controls: vec![control],
+    }
+}
+
+/// The Phoenix MAR basin at `level` m, its pump at 30 %.
+pub(crate) fn basin(level: f64) -> InfraNodeShardSnapshot {
+    let pump = ControlChannel { name: "pump".into(), unit: "%".into(), min: 0.0, max: 100.0, current: 30.0 };
+    shard("PHX-MAR-01", "basin_level", level, pump)
+}
+
+/// Default config over `horizon_steps` steps of 1 s.
+pub(crate) fn config(horizon_steps: usize) -> MpcRuntimeConfig {
+    MpcRuntimeConfig {
+        horizon: MpcHorizonConfig { horizon_steps, dt_seconds: 1.0, max_iterations: 50 },
+        ..Default::default()
+    }
+}
+
+/// Driving the actuator costs exergy and does nothing else.
+pub(crate) fn exergy_model() -> AffineKerModel {
+    AffineKerModel {
+        base: KerPrediction { eco: 0.6, ..Default::default() },
+        gain: vec![KerPrediction { ker: KerVector { exergy_cost: 1.0, ..Default::default() }, ..Default::default() }],
+        state_gain: Vec::new(),
+    }
+}
+
+/// SLA misses start at 1 and fall with the actuator at `gain`.
+pub(crate) fn sla_model(gain: f64) -> AffineKerModel {
+    AffineKerModel {
+        base: KerPrediction { ker: KerVector { sla_violation_ratio: 1.0, ..Default::default() }, eco: 0.6, risk: 0.1 },
+        gain: vec![KerPrediction { ker: KerVector { sla_violation_ratio: -gain, ..Default::default() }, ..Default::default() }],
+        state_gain: Vec::new(),
+    }
+}
+
+/// Runtime with an allow-equal Lyapunov check and a 0.5 risk / 0.2 eco guard.
+pub(crate) fn runtime_with<S: MpcSolver>(cfg: MpcRuntimeConfig, solver: S) -> MpcRuntime<S> {
+    MpcRuntime::new(
+        cfg,
+        solver,
+        CorridorCheck,
+        LyapunovResidualChecker { allow_equal: true },
+        BiocompatGuard::new(BiocompatGuardConfig { max_bio_risk: 0.5, min_bio_eco: 0.2 }),
+    )
+}
+
+/// [`runtime_with`] a projected-gradient solver over `model`.
+pub(crate) fn runtime(cfg: MpcRuntimeConfig, model: AffineKerModel) -> PgRuntime {
+    runtime_with(cfg, ProjectedGradientSolver::new(ProjectedGradientConfig::default(), model))
+}
+
+/// Objective with an eco floor of 0.2, a risk ceiling of 0.9 and `weights`.
+pub(crate) fn objective(weights: ObjectiveTermWeights) -> BiocompatObjective {
+    BiocompatObjective::new_checked(BiocompatObjectiveConfig {
+        e_min: 0.2,
+        r_max: 0.9,
+        forbid_distress_coupling: true,
+        max_cognitive_load: None,
+        cognitive_load_index: None,
+        weights,
+        rewards: Vec::new(),
+    })
+    .unwrap()
+}
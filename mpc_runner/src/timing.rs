@@ -0,0 +1,82 @@
+//! Wall-clock statistics for MPC solves, kept over a rolling window so a
+//! long-running node reports its recent behaviour.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Number of most recent solves the percentiles are taken over.
+pub const SOLVE_TIME_WINDOW: usize = 1024;
+
+/// Solve-time summary; percentiles are nearest-rank over the last
+/// [`SOLVE_TIME_WINDOW`] solves, counts cover the runtime's lifetime.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SolveStats {
+    pub solves: u64,
+    pub timed_out: u64,
+    pub p50_seconds: f64,
+    pub p99_seconds: f64,
+    pub max_seconds: f64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SolveTimes {
+    samples: VecDeque<f64>,
+    solves: u64,
+    timed_out: u64,
+}
+
+impl SolveTimes {
+    pub(crate) fn record(&mut self, elapsed: Duration, timed_out: bool) {
+        if self.samples.len() == SOLVE_TIME_WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(elapsed.as_secs_f64());
+        self.solves += 1;
+        self.timed_out += u64::from(timed_out);
+    }
+
+    pub(crate) fn stats(&self) -> SolveStats {
+        let mut sorted: Vec<f64> = self.samples.iter().copied().collect();
+        sorted.sort_by(f64::total_cmp);
+        let rank = |p: f64| match sorted.len() {
+            0 => 0.0,
+            n => sorted[((p * n as f64).ceil() as usize).clamp(1, n) - 1],
+        };
+        SolveStats {
+            solves: self.solves,
+            timed_out: self.timed_out,
+            p50_seconds: rank(0.50),
+            p99_seconds: rank(0.99),
+            max_seconds: sorted.last().copied().unwrap_or(0.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles_over_window() {
+        let mut times = SolveTimes::default();
+        assert_eq!(times.stats(), SolveStats::default());
+
+        for ms in 1..=100 {
+            times.record(Duration::from_millis(ms), ms > 98);
+        }
+        let stats = times.stats();
+        assert_eq!((stats.solves, stats.timed_out), (100, 2));
+        assert!((stats.p50_seconds - 0.050).abs() < 1e-12);
+        assert!((stats.p99_seconds - 0.099).abs() < 1e-12);
+        assert!((stats.max_seconds - 0.100).abs() < 1e-12);
+
+        // Old samples leave the window; the counts keep them.
+        for _ in 0..SOLVE_TIME_WINDOW {
+            times.record(Duration::from_millis(2), false);
+        }
+        let stats = times.stats();
+        assert_eq!(stats.solves, 100 + SOLVE_TIME_WINDOW as u64);
+        assert_eq!(stats.max_seconds, 0.002);
+    }
+}
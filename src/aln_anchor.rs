@@ -1,19 +1,462 @@
-use serde_json::json;
+use chrono::{DateTime, Utc};
+use ring::digest::{digest, SHA512, SHA512_OUTPUT_LEN};
+use serde::{Serialize, Deserialize};
 use std::fs;
-use chrono::Utc;
-use ring::digest::{digest, SHA512};
-
-pub fn aln_serialize_commit(repo_state: &str, eco_proof: &str, did: &str) -> String {
-    let payload = json!({
-        "timestamp": Utc::now().to_rfc3339(),
-        "repository_state": repo_state,
-        "eco_core_proof": eco_proof,
-        "author_did": did,
-    });
-    let encoded = serde_json::to_vec_pretty(&payload).unwrap();
-    let hash = digest(&SHA512, &encoded);
-    let hex_hash = hex::encode(hash);
-    let path = "/tmp/eco-sys-anchor.json";
-    fs::write(path, &encoded).expect("write failed");
-    hex_hash
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::aln_manifest::{AlnManifest, ManifestError, ManifestSerialization};
+use crate::attestation::SampleAttestation;
+use crate::canonical::{canonical_digest, canonical_timestamp, to_canonical_bytes};
+use crate::fs_util::write_atomic;
+
+pub const DEFAULT_ANCHOR_DIR: &str = "data-lake/eco-sys/anchors";
+pub const ANCHOR_INDEX_FILE: &str = "index.json";
+pub const HEX_BROTLI_ENCODING: &str = "hex+brotli";
+/// Extension of exported Typewriter records encoded per the manifest.
+pub const RECORD_EXTENSION: &str = "aln";
+/// Upper bound on a decoded record, so a small brotli frame cannot expand without limit.
+pub const MAX_RECORD_BYTES: u64 = 1 << 20;
+
+/// Anchor hashes are hex SHA-512: exactly 128 lowercase hex characters.
+/// Anything else is refused before it is joined onto a path.
+pub fn validate_anchor_hash(anchor_hash: &str) -> Result<(), AnchorError> {
+    let well_formed = anchor_hash.len() == 2 * SHA512_OUTPUT_LEN
+        && anchor_hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'));
+    if well_formed {
+        Ok(())
+    } else {
+        Err(AnchorError::InvalidAnchorHash(anchor_hash.to_string()))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum AnchorError {
+    #[error("io error on {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("malformed anchor file {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("serialization error: {0}")]
+    Serialize(#[from] serde_json::Error),
+    #[error("anchor {expected} on disk hashes to {found}")]
+    HashMismatch { expected: String, found: String },
+    #[error("manifest update failed: {0}")]
+    Manifest(#[from] ManifestError),
+    #[error("record codec does not support encoding {0:?}")]
+    UnsupportedEncoding(String),
+    #[error("brotli compression level {0} out of range 0..=11")]
+    InvalidCompressionLevel(u32),
+    #[error("brotli stream error: {0}")]
+    Compression(#[source] std::io::Error),
+    #[error("malformed encoded record: {0}")]
+    MalformedRecord(&'static str),
+    #[error("record checksum mismatch")]
+    ChecksumMismatch,
+    #[error("invalid anchor hash {0:?}, expected 128 lowercase hex characters")]
+    InvalidAnchorHash(String),
+}
+
+/// Encoder/decoder for the manifest's `hex+brotli` serialization.
+///
+/// Frame layout before hex encoding: `brotli(payload)`, followed by the 64-byte
+/// SHA-512 of the uncompressed payload when `checksum` is enabled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RecordCodec {
+    pub compression_level: u32,
+    pub checksum: bool,
+}
+
+impl RecordCodec {
+    pub fn new(compression_level: u32, checksum: bool) -> Result<Self, AnchorError> {
+        if compression_level > 11 {
+            return Err(AnchorError::InvalidCompressionLevel(compression_level));
+        }
+        Ok(RecordCodec { compression_level, checksum })
+    }
+
+    pub fn from_manifest(settings: &ManifestSerialization) -> Result<Self, AnchorError> {
+        if settings.encoding != HEX_BROTLI_ENCODING {
+            return Err(AnchorError::UnsupportedEncoding(settings.encoding.clone()));
+        }
+        Self::new(settings.compression_level, settings.checksum)
+    }
+
+    pub fn encode(&self, payload: &[u8]) -> Result<String, AnchorError> {
+        let mut frame = Vec::new();
+        {
+            let mut writer = brotli::CompressorWriter::new(&mut frame, 4096, self.compression_level, 22);
+            writer.write_all(payload).map_err(AnchorError::Compression)?;
+            writer.flush().map_err(AnchorError::Compression)?;
+        }
+        if self.checksum {
+            frame.extend_from_slice(digest(&SHA512, payload).as_ref());
+        }
+        Ok(hex::encode(frame))
+    }
+
+    pub fn decode(&self, encoded: &str) -> Result<Vec<u8>, AnchorError> {
+        let frame = hex::decode(encoded.trim()).map_err(|_| AnchorError::MalformedRecord("invalid hex"))?;
+        let (compressed, checksum) = if self.checksum {
+            if frame.len() < SHA512_OUTPUT_LEN {
+                return Err(AnchorError::MalformedRecord("frame shorter than checksum"));
+            }
+            let (body, sum) = frame.split_at(frame.len() - SHA512_OUTPUT_LEN);
+            (body, Some(sum))
+        } else {
+            (frame.as_slice(), None)
+        };
+
+        let mut payload = Vec::new();
+        brotli::Decompressor::new(compressed, 4096)
+            .take(MAX_RECORD_BYTES + 1)
+            .read_to_end(&mut payload)
+            .map_err(AnchorError::Compression)?;
+        if payload.len() as u64 > MAX_RECORD_BYTES {
+            return Err(AnchorError::MalformedRecord("decoded record exceeds size limit"));
+        }
+
+        if let Some(sum) = checksum {
+            if digest(&SHA512, &payload).as_ref() != sum {
+                return Err(AnchorError::ChecksumMismatch);
+            }
+        }
+        Ok(payload)
+    }
+
+    /// Encode the canonical bytes of an anchor record.
+    pub fn encode_record(&self, record: &AnchorRecord) -> Result<String, AnchorError> {
+        self.encode(&to_canonical_bytes(record)?)
+    }
+
+    pub fn decode_record(&self, encoded: &str) -> Result<AnchorRecord, AnchorError> {
+        Ok(serde_json::from_slice(&self.decode(encoded)?)?)
+    }
+}
+
+/// Provenance chain state when an anchor was written, so truncating or
+/// rewriting the journal chain later can be detected against the anchor.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JournalHead {
+    /// Provenance entries before the one that records this anchor.
+    pub entries: usize,
+    /// `entry_hash` of the last of those entries; `None` for an empty chain.
+    pub head_hash: Option<String>,
+}
+
+/// Payload of one ALN anchor; its canonical SHA-512 is the anchor hash.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AnchorRecord {
+    pub timestamp: String,
+    pub repository_state: String,
+    pub eco_core_proof: String,
+    pub author_did: String,
+    #[serde(default)]
+    pub signature: Option<String>,
+    #[serde(default)]
+    pub key_id: Option<String>,
+    #[serde(default)]
+    pub public_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub journal_head: Option<JournalHead>,
+    /// Merkle SHA-512 of the repository tree this anchor vouches for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo_digest: Option<String>,
+}
+
+impl AnchorRecord {
+    pub fn new(
+        repo_state: &str,
+        eco_proof: &str,
+        did: &str,
+        attestation: Option<&SampleAttestation>,
+        timestamp: DateTime<Utc>,
+    ) -> Self {
+        AnchorRecord {
+            timestamp: canonical_timestamp(timestamp),
+            repository_state: repo_state.to_string(),
+            eco_core_proof: eco_proof.to_string(),
+            author_did: did.to_string(),
+            signature: attestation.map(|a| a.signature.clone()),
+            key_id: attestation.map(|a| a.key_id.clone()),
+            public_key: attestation.map(|a| a.public_key.clone()),
+            journal_head: None,
+            repo_digest: None,
+        }
+    }
+
+    /// Record the repository Merkle digest that later validations compare against.
+    pub fn with_repo_digest(mut self, digest: &str) -> Self {
+        self.repo_digest = Some(digest.to_string());
+        self
+    }
+
+    /// Pin the journal's provenance chain as it stood before this anchor.
+    pub fn with_journal_head(mut self, head: JournalHead) -> Self {
+        self.journal_head = Some(head);
+        self
+    }
+
+    pub fn anchor_hash(&self) -> Result<String, AnchorError> {
+        Ok(canonical_digest(self)?)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AnchorIndexEntry {
+    pub anchor_hash: String,
+    pub timestamp: String,
+    pub file: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct AnchorIndex {
+    pub anchors: Vec<AnchorIndexEntry>,
+}
+
+/// Writes each anchor to `<dir>/<hash>.json` and keeps `<dir>/index.json` in sync.
+/// With a manifest attached, its `[anchors]` section is updated after every write.
+#[derive(Debug, Clone)]
+pub struct AnchorWriter {
+    dir: PathBuf,
+    manifest_path: Option<PathBuf>,
+}
+
+impl AnchorWriter {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        AnchorWriter { dir: dir.into(), manifest_path: None }
+    }
+
+    pub fn with_manifest(mut self, path: impl Into<PathBuf>) -> Self {
+        self.manifest_path = Some(path.into());
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn anchor_path(&self, anchor_hash: &str) -> Result<PathBuf, AnchorError> {
+        validate_anchor_hash(anchor_hash)?;
+        Ok(self.dir.join(format!("{anchor_hash}.json")))
+    }
+
+    pub fn index_path(&self) -> PathBuf {
+        self.dir.join(ANCHOR_INDEX_FILE)
+    }
+
+    /// Persist `record` and register it in the index. Re-writing an existing anchor is a no-op.
+    pub fn write(&self, record: &AnchorRecord) -> Result<String, AnchorError> {
+        self.write_unindexed(record)?;
+        self.register(record)
+    }
+
+    /// Persist `record` without registering it. Only indexed anchors are
+    /// checked against the journal, so a run that stops before
+    /// [`AnchorWriter::register`] leaves a stray file rather than a fault.
+    pub fn write_unindexed(&self, record: &AnchorRecord) -> Result<String, AnchorError> {
+        let anchor_hash = record.anchor_hash()?;
+        let encoded = to_canonical_bytes(record)?;
+        let path = self.anchor_path(&anchor_hash)?;
+        write_atomic(&path, &encoded).map_err(|source| AnchorError::Io { path, source })?;
+        Ok(anchor_hash)
+    }
+
+    /// Add an anchor written by [`AnchorWriter::write_unindexed`] to the index
+    /// and point the manifest at it. Registering it again is a no-op.
+    pub fn register(&self, record: &AnchorRecord) -> Result<String, AnchorError> {
+        let anchor_hash = record.anchor_hash()?;
+        let path = self.anchor_path(&anchor_hash)?;
+        let mut index = self.index()?;
+        if !index.anchors.iter().any(|e| e.anchor_hash == anchor_hash) {
+            index.anchors.push(AnchorIndexEntry {
+                anchor_hash: anchor_hash.clone(),
+                timestamp: record.timestamp.clone(),
+                file: format!("{anchor_hash}.json"),
+            });
+            let index_path = self.index_path();
+            let encoded = serde_json::to_vec_pretty(&index)?;
+            write_atomic(&index_path, &encoded)
+                .map_err(|source| AnchorError::Io { path: index_path, source })?;
+        }
+
+        if let Some(manifest_path) = &self.manifest_path {
+            let mut manifest = AlnManifest::load(manifest_path)?;
+            if manifest.serialization.encoding == HEX_BROTLI_ENCODING {
+                self.export_record(record, &anchor_hash, &RecordCodec::from_manifest(&manifest.serialization)?)?;
+            }
+            manifest.record_anchor(&record.repository_state, &anchor_hash, &path);
+            manifest.save(manifest_path)?;
+        }
+        Ok(anchor_hash)
+    }
+
+    pub fn record_path(&self, anchor_hash: &str) -> Result<PathBuf, AnchorError> {
+        validate_anchor_hash(anchor_hash)?;
+        Ok(self.dir.join(format!("{anchor_hash}.{RECORD_EXTENSION}")))
+    }
+
+    /// Write the encoded Typewriter record for an anchor next to its JSON file.
+    pub fn export_record(
+        &self,
+        record: &AnchorRecord,
+        anchor_hash: &str,
+        codec: &RecordCodec,
+    ) -> Result<PathBuf, AnchorError> {
+        let path = self.record_path(anchor_hash)?;
+        let encoded = codec.encode_record(record)?;
+        write_atomic(&path, encoded.as_bytes()).map_err(|source| AnchorError::Io { path: path.clone(), source })?;
+        Ok(path)
+    }
+
+    /// Current index; an absent index file means no anchors yet.
+    pub fn index(&self) -> Result<AnchorIndex, AnchorError> {
+        let path = self.index_path();
+        match fs::read_to_string(&path) {
+            Ok(data) => serde_json::from_str(&data).map_err(|source| AnchorError::Parse { path, source }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(AnchorIndex::default()),
+            Err(source) => Err(AnchorError::Io { path, source }),
+        }
+    }
+
+    /// Repository digest stored by the most recent anchor that carries one.
+    pub fn latest_repo_digest(&self) -> Result<Option<String>, AnchorError> {
+        for entry in self.index()?.anchors.iter().rev() {
+            if let Some(digest) = self.read(&entry.anchor_hash)?.repo_digest {
+                return Ok(Some(digest));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Read an anchor back and check that its content still hashes to its name.
+    pub fn read(&self, anchor_hash: &str) -> Result<AnchorRecord, AnchorError> {
+        let path = self.anchor_path(anchor_hash)?;
+        let data = fs::read(&path).map_err(|source| AnchorError::Io { path: path.clone(), source })?;
+        let record: AnchorRecord =
+            serde_json::from_slice(&data).map_err(|source| AnchorError::Parse { path, source })?;
+        let found = record.anchor_hash()?;
+        if found != anchor_hash {
+            return Err(AnchorError::HashMismatch { expected: anchor_hash.to_string(), found });
+        }
+        Ok(record)
+    }
+}
+
+impl Default for AnchorWriter {
+    fn default() -> Self {
+        Self::new(DEFAULT_ANCHOR_DIR)
+    }
+}
+
+pub fn aln_serialize_commit(
+    writer: &AnchorWriter,
+    repo_state: &str,
+    eco_proof: &str,
+    did: &str,
+    attestation: Option<&SampleAttestation>,
+    timestamp: DateTime<Utc>,
+) -> Result<String, AnchorError> {
+    writer.write(&AnchorRecord::new(repo_state, eco_proof, did, attestation, timestamp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_writer_names_files_by_hash_and_indexes() {
+        let dir = tempfile::tempdir().unwrap();
+        let writer = AnchorWriter::new(dir.path().join("anchors"));
+        let ts = Utc.with_ymd_and_hms(2026, 2, 28, 22, 41, 15).unwrap();
+
+        let first = aln_serialize_commit(&writer, "main@eco-sys-v1.0.0", "ab", "did:x", None, ts).unwrap();
+        let again = aln_serialize_commit(&writer, "main@eco-sys-v1.0.0", "ab", "did:x", None, ts).unwrap();
+        let second = aln_serialize_commit(&writer, "main@eco-sys-v1.0.1", "cd", "did:x", None, ts).unwrap();
+        assert_eq!(first, again);
+        assert_ne!(first, second);
+
+        let index = writer.index().unwrap();
+        assert_eq!(index.anchors.len(), 2);
+        assert_eq!(index.anchors[0].timestamp, "2026-02-28T22:41:15.000000Z");
+        assert_eq!(writer.read(&first).unwrap().repository_state, "main@eco-sys-v1.0.0");
+        assert_eq!(writer.latest_repo_digest().unwrap(), None);
+
+        fs::write(writer.anchor_path(&second).unwrap(), b"{}").unwrap();
+        assert!(matches!(writer.read(&second), Err(AnchorError::Parse { .. })));
+
+        for bad in ["../../etc/passwd", &first[..127], &first.to_uppercase(), &format!("{first}0")] {
+            assert!(matches!(writer.read(bad), Err(AnchorError::InvalidAnchorHash(_))));
+        }
+    }
+
+    #[test]
+    fn test_writer_updates_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest_path = dir.path().join("eco-sys.aln.toml");
+        fs::copy(crate::aln_manifest::DEFAULT_MANIFEST_PATH, &manifest_path).unwrap();
+        let writer = AnchorWriter::new(dir.path().join("anchors")).with_manifest(&manifest_path);
+
+        let hash = aln_serialize_commit(&writer, "refs/heads/main", "ab", "did:x", None, Utc::now()).unwrap();
+        let manifest = AlnManifest::load(&manifest_path).unwrap();
+        let codec = RecordCodec::from_manifest(&manifest.serialization).unwrap();
+        let exported = fs::read_to_string(writer.record_path(&hash).unwrap()).unwrap();
+        assert_eq!(codec.decode_record(&exported).unwrap(), writer.read(&hash).unwrap());
+        assert_eq!(manifest.anchors.root_commit, "git:refs/heads/main");
+        assert_eq!(manifest.anchors.eco_core_proof, format!("0x{hash}"));
+        assert_eq!(manifest.anchors.data_lake_entry, writer.anchor_path(&hash).unwrap().display().to_string());
+    }
+
+    #[test]
+    fn test_codec_round_trip() {
+        let payload = br#"{"repository_state":"main","eco_core_proof":"ab"}"#.repeat(20);
+        for (level, checksum) in [(0, false), (9, true), (11, true)] {
+            let codec = RecordCodec::new(level, checksum).unwrap();
+            let encoded = codec.encode(&payload).unwrap();
+            assert!(encoded.chars().all(|c| c.is_ascii_hexdigit()));
+            assert_eq!(codec.decode(&encoded).unwrap(), payload);
+        }
+
+        let record = AnchorRecord::new("main", "ab", "did:x", None, Utc::now());
+        let codec = RecordCodec::new(9, true).unwrap();
+        assert_eq!(codec.decode_record(&codec.encode_record(&record).unwrap()).unwrap(), record);
+    }
+
+    #[test]
+    fn test_codec_rejects_bad_frames() {
+        assert!(matches!(RecordCodec::new(12, true), Err(AnchorError::InvalidCompressionLevel(12))));
+
+        let codec = RecordCodec::new(9, true).unwrap();
+        let mut encoded = codec.encode(b"payload").unwrap();
+        let last = encoded.pop().unwrap();
+        encoded.push(if last == '0' { '1' } else { '0' });
+        assert!(matches!(codec.decode(&encoded), Err(AnchorError::ChecksumMismatch)));
+        assert!(matches!(codec.decode("zz"), Err(AnchorError::MalformedRecord(_))));
+        assert!(matches!(codec.decode("abcd"), Err(AnchorError::MalformedRecord(_))));
+
+        // A few bytes of brotli that expand past the cap are refused.
+        let bomb = RecordCodec::new(11, false).unwrap().encode(&vec![0u8; MAX_RECORD_BYTES as usize + 1]).unwrap();
+        assert!(bomb.len() < 1024);
+        let unchecked = RecordCodec::new(11, false).unwrap();
+        assert!(matches!(unchecked.decode(&bomb), Err(AnchorError::MalformedRecord(_))));
+    }
+
+    #[test]
+    fn test_unwritable_dir_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let blocker = dir.path().join("file");
+        fs::write(&blocker, b"").unwrap();
+        let writer = AnchorWriter::new(blocker.join("anchors"));
+        let record = AnchorRecord::new("s", "p", "d", None, Utc::now());
+        assert!(matches!(writer.write(&record), Err(AnchorError::Io { .. })));
+    }
 }
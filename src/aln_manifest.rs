@@ -0,0 +1,260 @@
+use serde::{Serialize, Deserialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use toml::Table;
+
+use crate::fs_util::write_atomic;
+use crate::attestation::TrustedSigner;
+
+pub const DEFAULT_MANIFEST_PATH: &str = "manifests/eco-sys.aln.toml";
+
+/// Hash formats the anchoring code can produce.
+pub const SUPPORTED_HASH_FORMATS: &[&str] = &["SHA-512"];
+/// Record encodings the anchoring code can produce.
+pub const SUPPORTED_ENCODINGS: &[&str] = &["json", "hex+brotli"];
+
+#[derive(Debug, Error)]
+pub enum ManifestError {
+    #[error("io error on {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("malformed manifest {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+    #[error("serialization error: {0}")]
+    Serialize(#[from] toml::ser::Error),
+    #[error("unsupported hash_format {0:?}, expected one of {SUPPORTED_HASH_FORMATS:?}")]
+    UnsupportedHashFormat(String),
+    #[error("unsupported encoding {0:?}, expected one of {SUPPORTED_ENCODINGS:?}")]
+    UnsupportedEncoding(String),
+    #[error("compression_level {0} out of range 0..=11")]
+    InvalidCompressionLevel(u32),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ManifestMeta {
+    pub name: String,
+    pub version: String,
+    pub created_at: String,
+    pub framework: String,
+    pub hash_format: String,
+    pub chain: String,
+    /// Keys this version does not know, kept so a rewrite does not drop them.
+    #[serde(flatten)]
+    pub extra: Table,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ManifestAuthors {
+    pub maintainer: String,
+    pub did: String,
+    pub attestation: String,
+    pub record_type: String,
+    /// Hex Ed25519 public keys allowed to sign for `did`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub public_keys: Vec<String>,
+    /// Keys this version does not know, kept so a rewrite does not drop them.
+    #[serde(flatten)]
+    pub extra: Table,
+}
+
+impl ManifestAuthors {
+    pub fn trusted_signer(&self) -> TrustedSigner {
+        TrustedSigner::new(&self.did, self.public_keys.clone())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ManifestAnchors {
+    pub root_commit: String,
+    pub eco_core_proof: String,
+    pub data_lake_entry: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub virta_sys_anchor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artemis_integration: Option<String>,
+    /// Keys this version does not know, kept so a rewrite does not drop them.
+    #[serde(flatten)]
+    pub extra: Table,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ManifestSerialization {
+    pub encoding: String,
+    pub compression_level: u32,
+    pub checksum: bool,
+    pub timestamp: bool,
+    /// Keys this version does not know, kept so a rewrite does not drop them.
+    #[serde(flatten)]
+    pub extra: Table,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ManifestEnergy {
+    pub machine_count: u32,
+    pub baseline_x_mwz: f64,
+    pub baseline_y_mwz: f64,
+    pub target_utilization: f64,
+    pub compliance_mode: String,
+    /// Keys this version does not know, kept so a rewrite does not drop them.
+    #[serde(flatten)]
+    pub extra: Table,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ManifestAudit {
+    pub ledger_node: String,
+    pub proof_format: String,
+    pub export_mode: String,
+    /// Keys this version does not know, kept so a rewrite does not drop them.
+    #[serde(flatten)]
+    pub extra: Table,
+}
+
+/// Typed view of `manifests/eco-sys.aln.toml`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AlnManifest {
+    pub meta: ManifestMeta,
+    pub authors: ManifestAuthors,
+    pub anchors: ManifestAnchors,
+    pub serialization: ManifestSerialization,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub energy: Option<ManifestEnergy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audit: Option<ManifestAudit>,
+    /// Sections and keys this version does not know, kept so a rewrite does
+    /// not drop them. Comments other than the leading banner are not kept.
+    #[serde(flatten)]
+    pub extra: Table,
+    /// Leading comment block, kept so a rewrite does not drop the file banner.
+    #[serde(skip)]
+    pub header: String,
+}
+
+impl AlnManifest {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ManifestError> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path).map_err(|source| ManifestError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::parse(&data).map_err(|source| ManifestError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    pub fn parse(data: &str) -> Result<Self, toml::de::Error> {
+        let mut manifest: AlnManifest = toml::from_str(data)?;
+        manifest.header = data
+            .lines()
+            .take_while(|l| l.starts_with('#'))
+            .map(|l| format!("{l}\n"))
+            .collect();
+        Ok(manifest)
+    }
+
+    pub fn to_toml_string(&self) -> Result<String, ManifestError> {
+        let body = toml::to_string(self)?;
+        if self.header.is_empty() {
+            Ok(body)
+        } else {
+            Ok(format!("{}\n{}", self.header, body))
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ManifestError> {
+        let path = path.as_ref();
+        let encoded = self.to_toml_string()?;
+        write_atomic(path, encoded.as_bytes()).map_err(|source| ManifestError::Io {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    /// Reject settings the anchoring code cannot honour.
+    pub fn validate(&self) -> Result<(), ManifestError> {
+        if !SUPPORTED_HASH_FORMATS.contains(&self.meta.hash_format.as_str()) {
+            return Err(ManifestError::UnsupportedHashFormat(self.meta.hash_format.clone()));
+        }
+        if !SUPPORTED_ENCODINGS.contains(&self.serialization.encoding.as_str()) {
+            return Err(ManifestError::UnsupportedEncoding(self.serialization.encoding.clone()));
+        }
+        if self.serialization.compression_level > 11 {
+            return Err(ManifestError::InvalidCompressionLevel(self.serialization.compression_level));
+        }
+        Ok(())
+    }
+
+    /// Point the `[anchors]` section at the most recent anchor.
+    pub fn record_anchor(&mut self, repository_state: &str, anchor_hash: &str, anchor_path: &Path) {
+        self.anchors.root_commit = format!("git:{repository_state}");
+        self.anchors.eco_core_proof = format!("0x{anchor_hash}");
+        self.anchors.data_lake_entry = if anchor_path.is_absolute() {
+            anchor_path.display().to_string()
+        } else {
+            format!("/{}", anchor_path.display())
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repo_manifest_round_trip() {
+        let manifest = AlnManifest::load(DEFAULT_MANIFEST_PATH).unwrap();
+        assert_eq!(manifest.meta.hash_format, "SHA-512");
+        assert_eq!(manifest.serialization.compression_level, 9);
+        assert!(manifest.header.starts_with("# ===="));
+
+        let reparsed = AlnManifest::parse(&manifest.to_toml_string().unwrap()).unwrap();
+        assert_eq!(reparsed, manifest);
+    }
+
+    #[test]
+    fn test_unknown_keys_and_sections_survive_save() {
+        let data = fs::read_to_string(DEFAULT_MANIFEST_PATH).unwrap()
+            + "\n[retention]\ndays = 365\nreviewed_at = 2026-03-01T00:00:00Z\n\n[retention.cold]\ntier = \"glacier\"\n";
+        let data = data.replace("[audit]\n", "[audit]\nwitness = \"node-7\"\n");
+        let manifest = AlnManifest::parse(&data).unwrap();
+        assert_eq!(manifest.audit.as_ref().unwrap().extra["witness"].as_str(), Some("node-7"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("eco-sys.aln.toml");
+        manifest.save(&path).unwrap();
+        let reloaded = AlnManifest::load(&path).unwrap();
+        assert_eq!(reloaded, manifest);
+        assert_eq!(reloaded.extra["retention"]["days"].as_integer(), Some(365));
+        assert_eq!(reloaded.extra["retention"]["cold"]["tier"].as_str(), Some("glacier"));
+        assert!(reloaded.extra["retention"]["reviewed_at"].is_datetime());
+    }
+
+    #[test]
+    fn test_validate_and_record_anchor() {
+        let mut manifest = AlnManifest::load(DEFAULT_MANIFEST_PATH).unwrap();
+        assert!(manifest.validate().is_ok());
+        manifest.serialization.compression_level = 12;
+        assert!(matches!(manifest.validate(), Err(ManifestError::InvalidCompressionLevel(12))));
+        manifest.serialization.compression_level = 9;
+
+        manifest.meta.hash_format = "MD5".to_string();
+        assert!(matches!(manifest.validate(), Err(ManifestError::UnsupportedHashFormat(_))));
+        manifest.meta.hash_format = "SHA-512".to_string();
+        manifest.serialization.encoding = "base64+zstd".to_string();
+        assert!(matches!(manifest.validate(), Err(ManifestError::UnsupportedEncoding(_))));
+
+        manifest.record_anchor("main", "abcd", Path::new("data-lake/eco-sys/anchors/abcd.json"));
+        assert_eq!(manifest.anchors.root_commit, "git:main");
+        assert_eq!(manifest.anchors.eco_core_proof, "0xabcd");
+        assert_eq!(manifest.anchors.data_lake_entry, "/data-lake/eco-sys/anchors/abcd.json");
+    }
+}
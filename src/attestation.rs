@@ -0,0 +1,239 @@
+use ring::digest::{digest, SHA512};
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use serde::{Serialize, Deserialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::eco_core::EnergySample;
+
+pub const DEFAULT_SIGNING_KEY_PATH: &str = "keys/eco-sys-ed25519.pk8";
+
+#[derive(Debug, Error)]
+pub enum AttestationError {
+    #[error("io error on {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("rejected PKCS#8 key: {0}")]
+    KeyRejected(String),
+    #[error("key generation failed")]
+    KeyGeneration,
+    #[error("malformed hex in {field}")]
+    Hex { field: &'static str },
+    #[error("key id {key_id} does not match public key")]
+    KeyIdMismatch { key_id: String },
+    #[error("Ed25519 signature does not verify")]
+    BadSignature,
+    #[error("attestation is bound to {did}, expected {expected}")]
+    UntrustedDid { did: String, expected: String },
+    #[error("public key of {key_id} is not pinned for this author")]
+    UntrustedKey { key_id: String },
+    #[error("{0} present but not signed")]
+    Unsigned(&'static str),
+}
+
+/// Key id binding an Ed25519 public key to an author DID:
+/// `<did>#ed25519-<first 16 hex chars of SHA-512(public key)>`.
+pub fn key_id_for(did: &str, public_key: &[u8]) -> String {
+    let fingerprint = hex::encode(digest(&SHA512, public_key));
+    format!("{}#ed25519-{}", did, &fingerprint[..16])
+}
+
+/// Author DID and the hex Ed25519 public keys pinned for it, from the manifest.
+/// Attestations are only trusted when they match both.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrustedSigner {
+    pub did: String,
+    pub public_keys: Vec<String>,
+}
+
+impl TrustedSigner {
+    pub fn new(did: impl Into<String>, public_keys: Vec<String>) -> Self {
+        TrustedSigner { did: did.into(), public_keys }
+    }
+
+    pub fn is_pinned(&self, public_key_hex: &str) -> bool {
+        self.public_keys.iter().any(|k| k.eq_ignore_ascii_case(public_key_hex))
+    }
+}
+
+/// Ed25519 signer loaded from a PKCS#8 v2 document.
+pub struct SampleSigner {
+    keypair: Ed25519KeyPair,
+    key_id: String,
+}
+
+impl SampleSigner {
+    pub fn from_pkcs8(pkcs8: &[u8], did: &str) -> Result<Self, AttestationError> {
+        let keypair = Ed25519KeyPair::from_pkcs8(pkcs8)
+            .map_err(|e| AttestationError::KeyRejected(e.to_string()))?;
+        let key_id = key_id_for(did, keypair.public_key().as_ref());
+        Ok(SampleSigner { keypair, key_id })
+    }
+
+    pub fn load_pkcs8(path: impl AsRef<Path>, did: &str) -> Result<Self, AttestationError> {
+        let path = path.as_ref();
+        let pkcs8 = fs::read(path).map_err(|source| AttestationError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::from_pkcs8(&pkcs8, did)
+    }
+
+    /// Generate a fresh keypair and write its PKCS#8 document to `path`,
+    /// readable by the owner only on unix. An existing file is never overwritten.
+    pub fn generate_pkcs8(path: impl AsRef<Path>, did: &str) -> Result<Self, AttestationError> {
+        let path = path.as_ref();
+        let rng = SystemRandom::new();
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng)
+            .map_err(|_| AttestationError::KeyGeneration)?;
+        let io_err = |source| AttestationError::Io { path: path.to_path_buf(), source };
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).map_err(io_err)?;
+        }
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        options
+            .open(path)
+            .and_then(|mut file| file.write_all(pkcs8.as_ref()))
+            .map_err(io_err)?;
+        Self::from_pkcs8(pkcs8.as_ref(), did)
+    }
+
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    pub fn public_key_hex(&self) -> String {
+        hex::encode(self.keypair.public_key().as_ref())
+    }
+
+    /// Detached attestation over arbitrary canonical bytes.
+    pub fn sign_payload(&self, payload: &[u8]) -> SampleAttestation {
+        SampleAttestation {
+            key_id: self.key_id.clone(),
+            public_key: self.public_key_hex(),
+            signature: hex::encode(self.keypair.sign(payload).as_ref()),
+        }
+    }
+
+    pub fn sign(&self, sample: EnergySample) -> SignedEnergySample {
+        SignedEnergySample { attestation: self.sign_payload(&sample.signing_payload()), sample }
+    }
+}
+
+/// Detached Ed25519 attestation over an `EnergySample`, as stored in the journal and ALN anchor.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SampleAttestation {
+    pub key_id: String,
+    pub public_key: String,
+    pub signature: String,
+}
+
+impl SampleAttestation {
+    /// Check the signature over `sample`, that `key_id` is derived from `public_key`,
+    /// and that both belong to `trusted`; the key id alone proves nothing.
+    pub fn verify(&self, sample: &EnergySample, trusted: &TrustedSigner) -> Result<(), AttestationError> {
+        self.verify_payload(&sample.signing_payload(), trusted)
+    }
+
+    /// Like `verify`, over the canonical bytes the signer was given.
+    pub fn verify_payload(&self, payload: &[u8], trusted: &TrustedSigner) -> Result<(), AttestationError> {
+        let public_key = hex::decode(&self.public_key)
+            .map_err(|_| AttestationError::Hex { field: "public_key" })?;
+        let signature = hex::decode(&self.signature)
+            .map_err(|_| AttestationError::Hex { field: "signature" })?;
+
+        let did = self.key_id.split('#').next().unwrap_or_default();
+        if key_id_for(did, &public_key) != self.key_id {
+            return Err(AttestationError::KeyIdMismatch { key_id: self.key_id.clone() });
+        }
+        if did != trusted.did {
+            return Err(AttestationError::UntrustedDid { did: did.to_string(), expected: trusted.did.clone() });
+        }
+        if !trusted.is_pinned(&self.public_key) {
+            return Err(AttestationError::UntrustedKey { key_id: self.key_id.clone() });
+        }
+
+        UnparsedPublicKey::new(&ED25519, &public_key)
+            .verify(payload, &signature)
+            .map_err(|_| AttestationError::BadSignature)
+    }
+
+    /// The author DID the key id is bound to.
+    pub fn did(&self) -> &str {
+        self.key_id.split('#').next().unwrap_or_default()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SignedEnergySample {
+    pub sample: EnergySample,
+    pub attestation: SampleAttestation,
+}
+
+impl SignedEnergySample {
+    pub fn verify(&self, trusted: &TrustedSigner) -> Result<(), AttestationError> {
+        self.attestation.verify(&self.sample, trusted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(unix)]
+    use std::os::unix::fs::PermissionsExt;
+
+    const DID: &str = "bostrom18sd2ujv24ual9c9pshtxys6j8knh6xaead9ye7";
+
+    #[test]
+    fn test_sign_and_verify() {
+        let dir = tempfile::tempdir().unwrap();
+        let key_path = dir.path().join("signer.pk8");
+        let signer = SampleSigner::generate_pkcs8(&key_path, DID).unwrap();
+        let reloaded = SampleSigner::load_pkcs8(&key_path, DID).unwrap();
+        assert_eq!(signer.key_id(), reloaded.key_id());
+        #[cfg(unix)]
+        assert_eq!(fs::metadata(&key_path).unwrap().permissions().mode() & 0o777, 0o600);
+        assert!(SampleSigner::generate_pkcs8(&key_path, DID).is_err());
+
+        let trusted = TrustedSigner::new(DID, vec![signer.public_key_hex()]);
+        let signed = reloaded.sign(EnergySample::new(1200.0, 900.0, 0.7));
+        assert!(signed.verify(&trusted).is_ok());
+        assert_eq!(signed.attestation.did(), DID);
+
+        let mut tampered = signed.clone();
+        tampered.sample.target_utilization = 0.9;
+        assert!(matches!(tampered.verify(&trusted), Err(AttestationError::BadSignature)));
+
+        let mut rebound = signed.clone();
+        rebound.attestation.key_id = key_id_for("bostrom1other", &[0u8; 32]);
+        assert!(matches!(rebound.verify(&trusted), Err(AttestationError::KeyIdMismatch { .. })));
+    }
+
+    #[test]
+    fn test_self_signed_attestation_is_not_trusted() {
+        let dir = tempfile::tempdir().unwrap();
+        let pinned = SampleSigner::generate_pkcs8(dir.path().join("pinned.pk8"), DID).unwrap();
+        let trusted = TrustedSigner::new(DID, vec![pinned.public_key_hex()]);
+
+        // A fresh key claiming the same DID is internally consistent but not pinned.
+        let forger = SampleSigner::generate_pkcs8(dir.path().join("forger.pk8"), DID).unwrap();
+        let forged = forger.sign(EnergySample::new(1200.0, 900.0, 0.7));
+        assert!(matches!(forged.verify(&trusted), Err(AttestationError::UntrustedKey { .. })));
+
+        let other = SampleSigner::generate_pkcs8(dir.path().join("other.pk8"), "bostrom1other").unwrap();
+        let signed = other.sign(EnergySample::new(1200.0, 900.0, 0.7));
+        let trusted = TrustedSigner::new(DID, vec![other.public_key_hex()]);
+        assert!(matches!(signed.verify(&trusted), Err(AttestationError::UntrustedDid { .. })));
+    }
+}
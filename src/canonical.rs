@@ -0,0 +1,136 @@
+//! Canonical encoding for every payload that is hashed or signed in `eco_sys`.
+//!
+//! Rules:
+//! - compact JSON, no insignificant whitespace
+//! - object keys sorted by their UTF-8 bytes
+//! - integers as plain decimal; floats in shortest round-trip form with a
+//!   fractional part (`735.0`, `0.7`, `1e-7`), `-0.0` written as `0.0`
+//! - non-finite floats become `null`, as in `serde_json`
+//! - timestamps are strings supplied by the caller, normalized with `canonical_timestamp`
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use ring::digest::{digest, SHA512};
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt::Write;
+
+/// RFC 3339, UTC, microsecond precision, `Z` suffix.
+pub fn canonical_timestamp(ts: DateTime<Utc>) -> String {
+    ts.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+pub fn to_canonical_string<T: Serialize + ?Sized>(value: &T) -> Result<String, serde_json::Error> {
+    let value = serde_json::to_value(value)?;
+    let mut out = String::new();
+    write_value(&mut out, &value);
+    Ok(out)
+}
+
+pub fn to_canonical_bytes<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, serde_json::Error> {
+    to_canonical_string(value).map(String::into_bytes)
+}
+
+/// Hex-encoded SHA-512 of the canonical encoding.
+pub fn canonical_digest<T: Serialize + ?Sized>(value: &T) -> Result<String, serde_json::Error> {
+    let bytes = to_canonical_bytes(value)?;
+    Ok(hex::encode(digest(&SHA512, &bytes)))
+}
+
+fn write_value(out: &mut String, value: &Value) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                write!(out, "{i}").unwrap();
+            } else if let Some(u) = n.as_u64() {
+                write!(out, "{u}").unwrap();
+            } else {
+                write_float(out, n.as_f64().unwrap_or(f64::NAN));
+            }
+        }
+        Value::String(s) => write_string(out, s),
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(out, item);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.as_bytes().cmp(b.0.as_bytes()));
+            out.push('{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(out, key);
+                out.push(':');
+                write_value(out, item);
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn write_float(out: &mut String, f: f64) {
+    if !f.is_finite() {
+        out.push_str("null");
+    } else if f == 0.0 {
+        out.push_str("0.0");
+    } else {
+        write!(out, "{f:?}").unwrap();
+    }
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push_str(&serde_json::to_string(s).unwrap());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eco_core::EnergySample;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    fn golden_sample() -> EnergySample {
+        let ts = Utc.with_ymd_and_hms(2026, 2, 28, 22, 41, 15).unwrap();
+        EnergySample::at(ts, 1200.0, 900.0, 0.7)
+    }
+
+    #[test]
+    fn test_key_order_and_whitespace_do_not_matter() {
+        let a: Value = serde_json::from_str(r#"{"b": 1, "a": {"d": 0.5, "c": [1, 2.0]}}"#).unwrap();
+        let b = json!({"a": {"c": [1, 2.0], "d": 0.5}, "b": 1});
+        assert_eq!(to_canonical_string(&a).unwrap(), r#"{"a":{"c":[1,2.0],"d":0.5},"b":1}"#);
+        assert_eq!(canonical_digest(&a).unwrap(), canonical_digest(&b).unwrap());
+    }
+
+    #[test]
+    fn test_float_formatting() {
+        let v = json!([735.0, 0.7, -0.0, 1e-7, 1e21, -2.5, f64::NAN]);
+        assert_eq!(
+            to_canonical_string(&v).unwrap(),
+            "[735.0,0.7,0.0,1e-7,1e21,-2.5,null]"
+        );
+    }
+
+    #[test]
+    fn test_golden_energy_sample() {
+        let sample = golden_sample();
+        assert_eq!(
+            to_canonical_string(&sample).unwrap(),
+            r#"{"baseline_x_mwz":1200.0,"baseline_y_mwz":900.0,"target_utilization":0.7,"timestamp":"2026-02-28T22:41:15.000000Z"}"#
+        );
+        assert_eq!(
+            sample.sign_sample(),
+            "cf9d9201c7d88c11f7d577e1e95fa57071afb0d02a523e2f7eb0c3d7383bd051\
+             70a26b0b7aeb63f91e77f01acaa36f6a25154bc85abd6155db30faebdcb23e85"
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use serde::{Serialize, Deserialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::aln_anchor::DEFAULT_ANCHOR_DIR;
+use crate::attestation::DEFAULT_SIGNING_KEY_PATH;
+
+pub const DEFAULT_CONFIG_PATH: &str = "virta-git.config.json";
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("io error on {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("malformed config {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RepositoryConfig {
+    pub name: String,
+    pub url: String,
+    pub branch: String,
+    pub sync_enabled: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EnergyOrchestration {
+    pub total_machines: u32,
+    pub baseline_x_mwz: f64,
+    pub baseline_y_mwz: f64,
+    pub target_utilization: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TypewriterSettings {
+    pub authorship_did: String,
+    pub journal_path: String,
+    pub aln_manifest_path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_key_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anchor_dir: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ComplianceSettings {
+    pub nonfiction_only: bool,
+    pub tamper_evident: bool,
+    pub blockchain_anchor: String,
+}
+
+/// Eco-Sys view of `virta-git.config.json`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EcoSysConfig {
+    pub config_version: String,
+    pub eco_sys_mode: bool,
+    #[serde(default)]
+    pub repositories: Vec<RepositoryConfig>,
+    pub energy_orchestration: EnergyOrchestration,
+    pub typewriter_settings: TypewriterSettings,
+    pub compliance: ComplianceSettings,
+    /// Directory the config was loaded from; config paths are rooted here.
+    #[serde(skip)]
+    pub root: PathBuf,
+}
+
+impl EcoSysConfig {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let mut config: EcoSysConfig = serde_json::from_str(&data).map_err(|source| ConfigError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        config.root = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Ok(config)
+    }
+
+    /// Resolve a repository-rooted config path such as `/data-lake/eco-sys/...`.
+    pub fn resolve(&self, path: &str) -> PathBuf {
+        self.root.join(path.trim_start_matches('/'))
+    }
+
+    pub fn journal_path(&self) -> PathBuf {
+        self.resolve(&self.typewriter_settings.journal_path)
+    }
+
+    pub fn manifest_path(&self) -> PathBuf {
+        self.resolve(&self.typewriter_settings.aln_manifest_path)
+    }
+
+    pub fn signing_key_path(&self) -> PathBuf {
+        self.resolve(self.typewriter_settings.signing_key_path.as_deref().unwrap_or(DEFAULT_SIGNING_KEY_PATH))
+    }
+
+    pub fn anchor_dir(&self) -> PathBuf {
+        self.resolve(self.typewriter_settings.anchor_dir.as_deref().unwrap_or(DEFAULT_ANCHOR_DIR))
+    }
+
+    /// Repository-relative paths that anchoring rewrites; they are kept out of
+    /// the repository digest so recording an anchor does not invalidate it.
+    pub fn generated_paths(&self) -> Vec<PathBuf> {
+        let settings = &self.typewriter_settings;
+        [
+            settings.journal_path.as_str(),
+            settings.aln_manifest_path.as_str(),
+            settings.anchor_dir.as_deref().unwrap_or(DEFAULT_ANCHOR_DIR),
+        ]
+        .into_iter()
+        .map(|p| PathBuf::from(p.trim_start_matches('/')))
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repo_config_parses() {
+        let config = EcoSysConfig::load(DEFAULT_CONFIG_PATH).unwrap();
+        assert_eq!(config.energy_orchestration.total_machines, 8);
+        assert_eq!(config.energy_orchestration.baseline_x_mwz, 1200.0);
+        assert!(config.compliance.tamper_evident);
+        assert_eq!(config.journal_path(), Path::new("data-lake/eco-sys/typewriter-journal.json"));
+        assert_eq!(config.manifest_path(), Path::new("manifests/eco-sys.aln.toml"));
+        assert_eq!(config.anchor_dir(), Path::new(DEFAULT_ANCHOR_DIR));
+    }
+}
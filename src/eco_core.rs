@@ -1,8 +1,10 @@
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
 use ring::digest::{digest, SHA512};
 
-#[derive(Serialize, Deserialize, Debug)]
+use crate::canonical::{canonical_timestamp, to_canonical_bytes};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct EnergySample {
     pub timestamp: String,
     pub baseline_x_mwz: f64,
@@ -12,8 +14,13 @@ pub struct EnergySample {
 
 impl EnergySample {
     pub fn new(bx: f64, by: f64, target: f64) -> Self {
+        Self::at(Utc::now(), bx, by, target)
+    }
+
+    /// Sample stamped with an explicit time, so its digest is reproducible.
+    pub fn at(ts: DateTime<Utc>, bx: f64, by: f64, target: f64) -> Self {
         EnergySample {
-            timestamp: Utc::now().to_rfc3339(),
+            timestamp: canonical_timestamp(ts),
             baseline_x_mwz: bx,
             baseline_y_mwz: by,
             target_utilization: target,
@@ -25,25 +32,23 @@ impl EnergySample {
         avg * self.target_utilization
     }
 
+    /// Bytes covered by both the SHA-512 digest and the Ed25519 signature.
+    pub fn signing_payload(&self) -> Vec<u8> {
+        to_canonical_bytes(self).unwrap()
+    }
+
     pub fn sign_sample(&self) -> String {
-        let serialized = serde_json::to_string(&self).unwrap();
-        let hash = digest(&SHA512, serialized.as_bytes());
+        let hash = digest(&SHA512, &self.signing_payload());
         hex::encode(hash)
     }
 }
 
-pub fn validate_repo_state(repo: &str, authorship_proof: &str) -> bool {
-    let repo_bytes = digest(&SHA512, repo.as_bytes());
-    let check = hex::encode(repo_bytes);
-    check.starts_with(&authorship_proof[0..8])
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
     #[test]
     fn test_efficiency_flow() {
         let sample = EnergySample::new(1200.0, 900.0, 0.7);
-        assert!(sample.compute_efficiency() > 1000.0);
+        assert!((sample.compute_efficiency() - 735.0).abs() < 1e-9);
     }
 }
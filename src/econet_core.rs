@@ -0,0 +1,180 @@
+//! Risk coordinates, the Lyapunov residual and the safe-step rule shared by every
+//! shard producer (docs/K_E_R_Grammar.md K.1.2, K.1.3 and E.2).
+
+use serde::{Serialize, Deserialize};
+
+/// Numerical tolerance ε in `V_{t+1} <= V_t + ε`.
+pub const LYAPUNOV_EPSILON: f64 = 1e-9;
+
+/// Which side of a parameter's range is safe (K.1.1).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Direction {
+    /// Safe when low, e.g. noise or water use.
+    Max,
+    /// Safe when high, e.g. residence time or cut quality.
+    Min,
+}
+
+/// Normalize a physical reading onto `[0,1]` per K.1.2.
+///
+/// For `Max`: `clip((x - r_min) / (r_max - r_min))`; for `Min`:
+/// `clip((r_max - x) / (r_max - r_min))`. 0 is ideal, 1 is the corridor edge or worse.
+/// A degenerate range or non-finite reading maps to 1.
+pub fn normalize(x: f64, r_min: f64, r_max: f64, direction: Direction) -> f64 {
+    let span = r_max - r_min;
+    if !x.is_finite() || !span.is_normal() {
+        return 1.0;
+    }
+    let r = match direction {
+        Direction::Max => (x - r_min) / span,
+        Direction::Min => (r_max - x) / span,
+    };
+    r.clamp(0.0, 1.0)
+}
+
+/// One normalized risk coordinate with its safe/gold/hard bands.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RiskCoord {
+    pub varid: String,
+    /// Normalized risk in `[0,1]`.
+    pub value: f64,
+    pub safe: f64,
+    pub gold: f64,
+    pub hard: f64,
+    pub weight: f64,
+    pub lyapchannel: u32,
+}
+
+impl RiskCoord {
+    pub fn within_safe(&self) -> bool {
+        self.value <= self.safe
+    }
+
+    pub fn within_gold(&self) -> bool {
+        self.value <= self.gold
+    }
+
+    /// The hard band is reached; E.2 rejects the move outright.
+    pub fn hard_violation(&self) -> bool {
+        !self.value.is_finite() || self.value >= self.hard
+    }
+}
+
+/// Residual `V_t = Σ w_j r_j` over a set of risk coordinates (K.1.3).
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Residual {
+    pub vt: f64,
+    pub weights: Vec<f64>,
+    pub rx: Vec<RiskCoord>,
+}
+
+impl Residual {
+    /// Build a residual weighted by each coordinate's own `weight`.
+    pub fn new(rx: Vec<RiskCoord>) -> Self {
+        let mut residual = Residual { vt: 0.0, weights: rx.iter().map(|r| r.weight).collect(), rx };
+        residual.recompute();
+        residual
+    }
+
+    /// Refresh `vt` from `rx` and `weights`. A coordinate without a matching
+    /// entry in `weights` falls back to its own `weight`.
+    pub fn recompute(&mut self) {
+        self.vt = self
+            .rx
+            .iter()
+            .enumerate()
+            .map(|(j, r)| self.weights.get(j).copied().unwrap_or(r.weight) * r.value)
+            .sum();
+    }
+
+    /// R.1 `corridor_ok`: no coordinate has reached its hard band.
+    pub fn corridor_ok(&self) -> bool {
+        !self.rx.iter().any(RiskCoord::hard_violation)
+    }
+
+    /// All coordinates are within their gold band.
+    pub fn gold_ok(&self) -> bool {
+        self.rx.iter().all(RiskCoord::within_gold)
+    }
+}
+
+/// Outcome of checking a candidate step against the previous residual.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CorridorDecision {
+    /// Every corridor holds and `V` did not rise.
+    Ok,
+    /// No hard band was hit but `V` rose; derate upstream.
+    Derate { vt_prev: f64, vt_next: f64 },
+    /// One or more coordinates reached their hard band; stop.
+    Stop { varids: Vec<String> },
+}
+
+impl CorridorDecision {
+    pub fn is_ok(&self) -> bool {
+        matches!(self, CorridorDecision::Ok)
+    }
+}
+
+/// E.2 admissibility: reject on any hard-band hit, derate when
+/// `V_{t+1} > V_t + ε`. An empty `prev` (no history yet) only gets the hard check.
+pub fn enforce_safe_step(prev: Residual, next: Residual) -> CorridorDecision {
+    let hard: Vec<String> = next
+        .rx
+        .iter()
+        .filter(|r| r.hard_violation())
+        .map(|r| r.varid.clone())
+        .collect();
+    if !hard.is_empty() {
+        return CorridorDecision::Stop { varids: hard };
+    }
+    if !prev.rx.is_empty() && next.vt > prev.vt + LYAPUNOV_EPSILON {
+        return CorridorDecision::Derate { vt_prev: prev.vt, vt_next: next.vt };
+    }
+    CorridorDecision::Ok
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coord(varid: &str, value: f64, weight: f64) -> RiskCoord {
+        RiskCoord { varid: varid.into(), value, safe: 0.3, gold: 0.6, hard: 1.0, weight, lyapchannel: 0 }
+    }
+
+    #[test]
+    fn test_normalize_both_directions() {
+        assert!((normalize(95.0, 0.0, 150.0, Direction::Max) - 95.0 / 150.0).abs() < 1e-12);
+        assert_eq!(normalize(200.0, 0.0, 150.0, Direction::Max), 1.0);
+        assert_eq!(normalize(-1.0, 0.0, 150.0, Direction::Max), 0.0);
+        assert!((normalize(3.0, 2.0, 4.0, Direction::Min) - 0.5).abs() < 1e-12);
+        assert_eq!(normalize(5.0, 2.0, 4.0, Direction::Min), 0.0);
+        assert_eq!(normalize(1.0, 2.0, 4.0, Direction::Min), 1.0);
+        assert_eq!(normalize(f64::NAN, 2.0, 4.0, Direction::Min), 1.0);
+        assert_eq!(normalize(1.0, 2.0, 2.0, Direction::Max), 1.0);
+    }
+
+    #[test]
+    fn test_recompute_and_safe_step() {
+        let mut prev = Residual::new(vec![coord("a", 0.5, 0.6), coord("b", 0.2, 0.4)]);
+        assert!((prev.vt - 0.38).abs() < 1e-12);
+        prev.weights = vec![1.0];
+        prev.recompute();
+        assert!((prev.vt - (0.5 + 0.4 * 0.2)).abs() < 1e-12);
+
+        let prev = Residual::new(vec![coord("a", 0.5, 0.6), coord("b", 0.2, 0.4)]);
+        let better = Residual::new(vec![coord("a", 0.4, 0.6), coord("b", 0.2, 0.4)]);
+        assert_eq!(enforce_safe_step(prev.clone(), better.clone()), CorridorDecision::Ok);
+        assert!(matches!(enforce_safe_step(better.clone(), prev.clone()), CorridorDecision::Derate { .. }));
+        assert_eq!(enforce_safe_step(Residual::default(), prev.clone()), CorridorDecision::Ok);
+
+        let hard = Residual::new(vec![coord("a", 0.0, 0.6), coord("b", 1.0, 0.4)]);
+        assert_eq!(
+            enforce_safe_step(prev, hard.clone()),
+            CorridorDecision::Stop { varids: vec!["b".into()] }
+        );
+        assert!(!hard.corridor_ok());
+        assert!(better.gold_ok());
+    }
+}
@@ -0,0 +1,308 @@
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::canonical::{canonical_timestamp, to_canonical_bytes};
+use crate::eco_core::EnergySample;
+
+const MS_PER_HOUR: f64 = 3_600_000.0;
+
+#[derive(Debug, Error)]
+pub enum LedgerError {
+    #[error("window length must be positive and fit in milliseconds, got {0}s")]
+    InvalidWindow(i64),
+    #[error("sample timestamp {0:?} is not RFC 3339")]
+    BadTimestamp(String),
+    #[error("io error on {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("malformed ledger line {line} in {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        line: usize,
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+/// One line of a ledger JSONL file: a sample tagged with the machine that produced it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LedgerEntry {
+    pub machine_id: String,
+    #[serde(flatten)]
+    pub sample: EnergySample,
+}
+
+/// Energy integrated over one aligned window for one machine.
+///
+/// Power between samples is interpolated linearly and integrated with the trapezoid
+/// rule, so `*_mwzh` fields are MWz·h. `mean_efficiency_mwz` is the time-weighted
+/// mean of `EnergySample::compute_efficiency` over the covered span.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EnergyWindow {
+    pub machine_id: String,
+    pub start: String,
+    pub end: String,
+    pub sample_count: usize,
+    pub covered_seconds: f64,
+    pub baseline_energy_mwzh: f64,
+    pub effective_energy_mwzh: f64,
+    pub mean_efficiency_mwz: f64,
+    pub utilization_ratio: f64,
+    pub peak_mwz: f64,
+    pub peak_at: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LedgerTotals {
+    pub machines: usize,
+    pub windows: usize,
+    pub baseline_energy_mwzh: f64,
+    pub effective_energy_mwzh: f64,
+    pub utilization_ratio: f64,
+    pub peak_mwz: f64,
+}
+
+/// Canonical bytes of integrated windows and their totals, as signed into a journal commit.
+pub fn windows_signing_payload(windows: &[EnergyWindow], totals: &LedgerTotals) -> Vec<u8> {
+    to_canonical_bytes(&(windows, totals)).unwrap()
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Point {
+    at_ms: i64,
+    effective_mwz: f64,
+    baseline_mwz: f64,
+}
+
+impl Point {
+    fn lerp(&self, other: &Point, at_ms: i64) -> Point {
+        let span = (other.at_ms - self.at_ms) as f64;
+        let f = if span > 0.0 { (at_ms - self.at_ms) as f64 / span } else { 0.0 };
+        Point {
+            at_ms,
+            effective_mwz: self.effective_mwz + f * (other.effective_mwz - self.effective_mwz),
+            baseline_mwz: self.baseline_mwz + f * (other.baseline_mwz - self.baseline_mwz),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Accum {
+    sample_count: usize,
+    covered_ms: i64,
+    baseline_mwzh: f64,
+    effective_mwzh: f64,
+    peak: Option<(f64, i64)>,
+}
+
+impl Accum {
+    fn observe_peak(&mut self, p: &Point) {
+        if self.peak.is_none_or(|(v, _)| p.effective_mwz > v) {
+            self.peak = Some((p.effective_mwz, p.at_ms));
+        }
+    }
+}
+
+/// Per-machine time series of energy samples, integrated over fixed windows
+/// aligned to the Unix epoch.
+#[derive(Debug, Clone)]
+pub struct EnergyLedger {
+    window_ms: i64,
+    machines: BTreeMap<String, Vec<Point>>,
+}
+
+impl EnergyLedger {
+    pub fn new(window_seconds: i64) -> Result<Self, LedgerError> {
+        let window_ms = window_seconds
+            .checked_mul(1000)
+            .filter(|ms| *ms > 0)
+            .ok_or(LedgerError::InvalidWindow(window_seconds))?;
+        Ok(EnergyLedger { window_ms, machines: BTreeMap::new() })
+    }
+
+    /// Read a JSONL file of `LedgerEntry` lines.
+    pub fn load_jsonl(path: impl AsRef<Path>, window_seconds: i64) -> Result<Self, LedgerError> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path).map_err(|source| LedgerError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let mut ledger = Self::new(window_seconds)?;
+        for (i, line) in data.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: LedgerEntry = serde_json::from_str(line).map_err(|source| LedgerError::Parse {
+                path: path.to_path_buf(),
+                line: i + 1,
+                source,
+            })?;
+            ledger.ingest(&entry.machine_id, &entry.sample)?;
+        }
+        Ok(ledger)
+    }
+
+    /// Add a sample; samples may arrive out of order.
+    pub fn ingest(&mut self, machine_id: &str, sample: &EnergySample) -> Result<(), LedgerError> {
+        let at = DateTime::parse_from_rfc3339(&sample.timestamp)
+            .map_err(|_| LedgerError::BadTimestamp(sample.timestamp.clone()))?;
+        let point = Point {
+            at_ms: at.timestamp_millis(),
+            effective_mwz: sample.compute_efficiency(),
+            baseline_mwz: (sample.baseline_x_mwz + sample.baseline_y_mwz) / 2.0,
+        };
+        let series = self.machines.entry(machine_id.to_string()).or_default();
+        let pos = series.partition_point(|p| p.at_ms <= point.at_ms);
+        series.insert(pos, point);
+        Ok(())
+    }
+
+    fn window_start(&self, at_ms: i64) -> i64 {
+        at_ms.div_euclid(self.window_ms) * self.window_ms
+    }
+
+    /// Windows for every machine, ordered by machine id then start time.
+    pub fn windows(&self) -> Vec<EnergyWindow> {
+        let mut accums: BTreeMap<(&str, i64), Accum> = BTreeMap::new();
+
+        for (machine, series) in &self.machines {
+            for p in series {
+                let acc = accums.entry((machine, self.window_start(p.at_ms))).or_default();
+                acc.sample_count += 1;
+                acc.observe_peak(p);
+            }
+            for pair in series.windows(2) {
+                let (a, b) = (&pair[0], &pair[1]);
+                let mut cur = *a;
+                while cur.at_ms < b.at_ms {
+                    let start = self.window_start(cur.at_ms);
+                    let next = a.lerp(b, (start + self.window_ms).min(b.at_ms));
+                    let hours = (next.at_ms - cur.at_ms) as f64 / MS_PER_HOUR;
+                    let acc = accums.entry((machine, start)).or_default();
+                    acc.covered_ms += next.at_ms - cur.at_ms;
+                    acc.effective_mwzh += 0.5 * (cur.effective_mwz + next.effective_mwz) * hours;
+                    acc.baseline_mwzh += 0.5 * (cur.baseline_mwz + next.baseline_mwz) * hours;
+                    acc.observe_peak(&cur);
+                    if next.at_ms < start + self.window_ms {
+                        acc.observe_peak(&next);
+                    }
+                    cur = next;
+                }
+            }
+        }
+
+        accums
+            .into_iter()
+            .map(|((machine, start), acc)| {
+                let covered_hours = acc.covered_ms as f64 / MS_PER_HOUR;
+                let (peak_mwz, peak_ms) = acc.peak.unwrap_or((0.0, start));
+                EnergyWindow {
+                    machine_id: machine.to_string(),
+                    start: ms_to_timestamp(start),
+                    end: ms_to_timestamp(start + self.window_ms),
+                    sample_count: acc.sample_count,
+                    covered_seconds: acc.covered_ms as f64 / 1000.0,
+                    baseline_energy_mwzh: acc.baseline_mwzh,
+                    effective_energy_mwzh: acc.effective_mwzh,
+                    mean_efficiency_mwz: if covered_hours > 0.0 { acc.effective_mwzh / covered_hours } else { peak_mwz },
+                    utilization_ratio: ratio(acc.effective_mwzh, acc.baseline_mwzh),
+                    peak_mwz,
+                    peak_at: ms_to_timestamp(peak_ms),
+                }
+            })
+            .collect()
+    }
+
+    pub fn totals(&self) -> LedgerTotals {
+        let windows = self.windows();
+        let baseline: f64 = windows.iter().map(|w| w.baseline_energy_mwzh).sum();
+        let effective: f64 = windows.iter().map(|w| w.effective_energy_mwzh).sum();
+        LedgerTotals {
+            machines: self.machines.len(),
+            windows: windows.len(),
+            baseline_energy_mwzh: baseline,
+            effective_energy_mwzh: effective,
+            utilization_ratio: ratio(effective, baseline),
+            peak_mwz: windows.iter().map(|w| w.peak_mwz).fold(0.0, f64::max),
+        }
+    }
+}
+
+fn ratio(num: f64, den: f64) -> f64 {
+    if den > 0.0 { num / den } else { 0.0 }
+}
+
+fn ms_to_timestamp(ms: i64) -> String {
+    canonical_timestamp(Utc.timestamp_millis_opt(ms).single().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(h: u32, m: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 1, h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_constant_load_over_two_windows() {
+        let mut ledger = EnergyLedger::new(3600).unwrap();
+        for h in 0..=2 {
+            ledger.ingest("node-a", &EnergySample::at(at(h, 0), 1200.0, 900.0, 0.7)).unwrap();
+        }
+        let windows = ledger.windows();
+        assert_eq!(windows.len(), 3);
+        for w in &windows[..2] {
+            assert!((w.effective_energy_mwzh - 735.0).abs() < 1e-9);
+            assert!((w.baseline_energy_mwzh - 1050.0).abs() < 1e-9);
+            assert!((w.utilization_ratio - 0.7).abs() < 1e-12);
+            assert!((w.mean_efficiency_mwz - 735.0).abs() < 1e-9);
+        }
+        assert_eq!(windows[0].start, "2026-03-01T00:00:00.000000Z");
+        assert_eq!(windows[2].covered_seconds, 0.0);
+    }
+
+    #[test]
+    fn test_ramp_split_at_boundary_and_peaks() {
+        let mut ledger = EnergyLedger::new(3600).unwrap();
+        // Out of order on purpose; utilization ramps 0.2 -> 0.8 across the 01:00 boundary.
+        ledger.ingest("node-a", &EnergySample::at(at(1, 30), 1000.0, 1000.0, 0.8)).unwrap();
+        ledger.ingest("node-a", &EnergySample::at(at(0, 30), 1000.0, 1000.0, 0.2)).unwrap();
+        ledger.ingest("node-b", &EnergySample::at(at(0, 0), 500.0, 500.0, 1.0)).unwrap();
+        ledger.ingest("node-b", &EnergySample::at(at(0, 30), 500.0, 500.0, 1.0)).unwrap();
+
+        let windows = ledger.windows();
+        let a: Vec<_> = windows.iter().filter(|w| w.machine_id == "node-a").collect();
+        assert_eq!(a.len(), 2);
+        // 00:30-01:00: 200 -> 500 MWz over 0.5 h; 01:00-01:30: 500 -> 800 MWz.
+        assert!((a[0].effective_energy_mwzh - 175.0).abs() < 1e-9);
+        assert!((a[1].effective_energy_mwzh - 325.0).abs() < 1e-9);
+        assert_eq!(a[0].peak_mwz, 200.0);
+        assert_eq!(a[1].peak_mwz, 800.0);
+        assert_eq!(a[1].peak_at, "2026-03-01T01:30:00.000000Z");
+
+        let totals = ledger.totals();
+        assert_eq!(totals.machines, 2);
+        assert!((totals.effective_energy_mwzh - 750.0).abs() < 1e-9);
+        assert!((totals.baseline_energy_mwzh - 1250.0).abs() < 1e-9);
+        assert_eq!(totals.peak_mwz, 800.0);
+    }
+
+    #[test]
+    fn test_rejects_bad_input() {
+        assert!(matches!(EnergyLedger::new(0), Err(LedgerError::InvalidWindow(0))));
+        assert!(matches!(EnergyLedger::new(-60), Err(LedgerError::InvalidWindow(-60))));
+        assert!(matches!(EnergyLedger::new(i64::MAX / 10), Err(LedgerError::InvalidWindow(_))));
+        let mut sample = EnergySample::new(1.0, 1.0, 1.0);
+        sample.timestamp = "yesterday".to_string();
+        let mut ledger = EnergyLedger::new(60).unwrap();
+        assert!(matches!(ledger.ingest("n", &sample), Err(LedgerError::BadTimestamp(_))));
+    }
+}
@@ -1,6 +1,8 @@
 // Hex-stamp placeholder (HydroMower2026v1)
 // 0xhydro_mower_2026_v1_eca17f9b42de901277aa55cc33991188
 #![forbid(unsafe_code)]
+// `violation_residual_Vt` mirrors the shard CSV column name.
+#![allow(non_snake_case)]
 
 use crate::econet_core::{RiskCoord, Residual, CorridorDecision, enforce_safe_step};
 use crate::region::RegionConfig;
@@ -73,111 +75,56 @@ pub struct HydroMowerShardRow {
     pub notes: String,
 }
 
-/// Map raw measurements to normalized risk coordinates.
-/// Numbers here are placeholders; you will calibrate using Phoenix baselines.
-fn score_hydromower_run(run: &HydroMowerRun, region: &dyn RegionConfig) -> HydroMowerScore {
-    // Example corridor-normalization helpers (linear ramps).
-    fn ramp(x: f64, safe: f64, hard: f64) -> f64 {
-        if x <= safe {
-            0.0
-        } else if x >= hard {
-            1.0
-        } else {
-            (x - safe) / (hard - safe)
-        }
-    }
+/// Risk coordinates for a scored run, banded by the region.
+fn hydromower_coords(score: &HydroMowerScore, region: &dyn RegionConfig) -> Vec<RiskCoord> {
+    vec![
+        region.risk_coord("rcutquality", score.rcutquality),
+        region.risk_coord("rwateruse", score.rwateruse),
+        region.risk_coord("rnoise", score.rnoise),
+        region.risk_coord("rspraydrift", score.rspraydrift),
+        region.risk_coord("rsafety", score.rsafety),
+    ]
+}
 
+/// Map raw measurements to normalized risk coordinates using the region's ramps.
+fn score_hydromower_run(run: &HydroMowerRun, region: &dyn RegionConfig) -> HydroMowerScore {
     // rwateruse: compare to "baseline irrigation + mowing" corridor.
-    // You will replace constants with RegionConfig methods later.
-    let rwateruse = ramp(run.wateruse_l_per_m2, 2.0, 8.0);
+    let rwateruse = region.wateruse_ramp().risk(run.wateruse_l_per_m2);
 
-    // rnoise: quiet ≤ 70 dBA, hard band at 90 dBA.
-    let rnoise = ramp(run.noise_dba, 70.0, 90.0);
+    // rnoise: quiet at the region's safe level, hard band at its limit.
+    let rnoise = region.noise_ramp().risk(run.noise_dba);
 
-    // rspraydrift: drift radius ≤ 0.5 m safe, ≥ 2 m hard.
-    let rspraydrift = ramp(run.spray_drift_m, 0.5, 2.0);
+    // rspraydrift: drift radius against the region's safe/hard radius.
+    let rspraydrift = region.spraydrift_ramp().risk(run.spray_drift_m);
 
     // rcutquality: you will compute this from field cut-height variance;
     // placeholder uses wateruse as proxy for now.
     let rcutquality = rwateruse.min(1.0);
 
     // rsafety: a composite of pressure, exposed jets, and containment.
-    let rsafety = if run.nozzle_pressure_bar <= 6.0 { 0.2 } else { 0.8 };
-
-    // Aggregate into Residual for Lyapunov-style safety.
-    let rx = vec![
-        RiskCoord {
-            varid: "rcutquality".to_string(),
-            value: rcutquality,
-            safe: 0.3,
-            gold: 0.6,
-            hard: 1.0,
-            weight: 0.25,
-            lyapchannel: 30,
-        },
-        RiskCoord {
-            varid: "rwateruse".to_string(),
-            value: rwateruse,
-            safe: 0.3,
-            gold: 0.6,
-            hard: 1.0,
-            weight: 0.25,
-            lyapchannel: 31,
-        },
-        RiskCoord {
-            varid: "rnoise".to_string(),
-            value: rnoise,
-            safe: 0.3,
-            gold: 0.7,
-            hard: 1.0,
-            weight: 0.20,
-            lyapchannel: 32,
-        },
-        RiskCoord {
-            varid: "rspraydrift".to_string(),
-            value: rspraydrift,
-            safe: 0.2,
-            gold: 0.6,
-            hard: 1.0,
-            weight: 0.15,
-            lyapchannel: 33,
-        },
-        RiskCoord {
-            varid: "rsafety".to_string(),
-            value: rsafety,
-            safe: 0.1,
-            gold: 0.5,
-            hard: 1.0,
-            weight: 0.15,
-            lyapchannel: 34,
-        },
-    ];
-
-    let mut residual = Residual {
-        vt: 0.0,
-        weights: rx.iter().map(|r| r.weight).collect(),
-        rx,
-    };
-    residual.recompute();
-
-    // Ecoimpact: simple placeholder comparing to gas mower fuel use.
-    // Later you can tie this to CEIM LCA for gas vs hydro vs electric.
-    let ecoimpact_01 = (1.0 - rwateruse).max(0.0);
-
-    // Risk-of-harm as weighted sum of rx (already folded into vt).
-    let riskofharm_01 = residual.vt.min(1.0);
+    let rsafety = if run.nozzle_pressure_bar <= region.nozzle_pressure_safe_bar() { 0.2 } else { 0.8 };
 
-    HydroMowerScore {
+    let mut score = HydroMowerScore {
         rcutquality,
         rwateruse,
         rnoise,
         rspraydrift,
         rsafety,
-        ecoimpact_01,
-        riskofharm_01,
+        // Ecoimpact: simple placeholder comparing to gas mower fuel use.
+        // Later you can tie this to CEIM LCA for gas vs hydro vs electric.
+        ecoimpact_01: (1.0 - rwateruse).max(0.0),
+        riskofharm_01: 0.0,
         knowledgefactor_01: 0.93,
-        violation_residual_Vt: residual.vt,
-    }
+        violation_residual_Vt: 0.0,
+    };
+
+    // Aggregate into Residual for Lyapunov-style safety.
+    let residual = Residual::new(hydromower_coords(&score, region));
+
+    // Risk-of-harm as weighted sum of rx (already folded into vt).
+    score.riskofharm_01 = residual.vt.min(1.0);
+    score.violation_residual_Vt = residual.vt;
+    score
 }
 
 /// Convert run + score into a shard row and enforce ecosafety invariants.
@@ -192,59 +139,7 @@ pub fn hydromower_to_shard_row(
     let score = score_hydromower_run(run, region);
 
     // Rebuild residual to feed into enforce_safe_step.
-    let rx = vec![
-        RiskCoord {
-            varid: "rcutquality".to_string(),
-            value: score.rcutquality,
-            safe: 0.3,
-            gold: 0.6,
-            hard: 1.0,
-            weight: 0.25,
-            lyapchannel: 30,
-        },
-        RiskCoord {
-            varid: "rwateruse".to_string(),
-            value: score.rwateruse,
-            safe: 0.3,
-            gold: 0.6,
-            hard: 1.0,
-            weight: 0.25,
-            lyapchannel: 31,
-        },
-        RiskCoord {
-            varid: "rnoise".to_string(),
-            value: score.rnoise,
-            safe: 0.3,
-            gold: 0.7,
-            hard: 1.0,
-            weight: 0.20,
-            lyapchannel: 32,
-        },
-        RiskCoord {
-            varid: "rspraydrift".to_string(),
-            value: score.rspraydrift,
-            safe: 0.2,
-            gold: 0.6,
-            hard: 1.0,
-            weight: 0.15,
-            lyapchannel: 33,
-        },
-        RiskCoord {
-            varid: "rsafety".to_string(),
-            value: score.rsafety,
-            safe: 0.1,
-            gold: 0.5,
-            hard: 1.0,
-            weight: 0.15,
-            lyapchannel: 34,
-        },
-    ];
-    let mut next_residual = Residual {
-        vt: 0.0,
-        weights: rx.iter().map(|r| r.weight).collect(),
-        rx,
-    };
-    next_residual.recompute();
+    let next_residual = Residual::new(hydromower_coords(&score, region));
 
     let decision = enforce_safe_step(prev_residual.clone(), next_residual.clone());
 
@@ -281,3 +176,47 @@ pub fn hydromower_to_shard_row(
 
     (decision, row, next_residual)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::region::{FileRegionConfig, DEFAULT_REGION_DIR};
+    use std::path::Path;
+
+    fn run(noise_dba: f64) -> HydroMowerRun {
+        HydroMowerRun {
+            nodeid: "HYDRO-MOWER-PHX-01".into(),
+            region: "Phoenix-AZ-US".into(),
+            lat: 33.45,
+            lon: -112.07,
+            cutarea_m2_per_cycle: 400.0,
+            nozzle_pressure_bar: 5.0,
+            flow_l_per_min: 12.0,
+            runtime_min: 30.0,
+            wateruse_l_per_m2: 3.0,
+            energy_equiv_kwh: 0.8,
+            noise_dba,
+            spray_drift_m: 0.4,
+        }
+    }
+
+    #[test]
+    fn test_region_bands_drive_decision() {
+        let phx = FileRegionConfig::load(Path::new(DEFAULT_REGION_DIR).join("Phoenix-AZ-US.toml")).unwrap();
+        let tempe = FileRegionConfig::load(Path::new(DEFAULT_REGION_DIR).join("Tempe-AZ-US.toml")).unwrap();
+
+        let (decision, row, residual) =
+            hydromower_to_shard_row(&Residual::default(), &run(72.0), &phx, "0x00".into(), String::new());
+        assert!(decision.is_ok());
+        assert!((row.rnoise_01 - 0.1).abs() < 1e-12);
+        assert_eq!(row.violation_residual_Vt, residual.vt);
+
+        // Same run is noisier relative to Tempe's lakefront corridor, so V rises.
+        let (decision, row, _) = hydromower_to_shard_row(&residual, &run(72.0), &tempe, "0x00".into(), String::new());
+        assert!(row.rnoise_01 > 0.3);
+        assert!(matches!(decision, CorridorDecision::Derate { .. }));
+
+        let (decision, _, _) = hydromower_to_shard_row(&residual, &run(95.0), &phx, "0x00".into(), String::new());
+        assert_eq!(decision, CorridorDecision::Stop { varids: vec!["rnoise".into()] });
+    }
+}
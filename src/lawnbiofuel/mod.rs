@@ -1,6 +1,8 @@
 // Hex-stamp placeholder (LawnBiofuelPhoenix2026v1)
 // 0xlawn_biofuel_2026_v1_eca17f9b42de901277aa55cc33991188
 #![forbid(unsafe_code)]
+// `violation_residual_Vt` mirrors the shard CSV column name.
+#![allow(non_snake_case)]
 
 use crate::econet_core::{RiskCoord, Residual, CorridorDecision, enforce_safe_step};
 use crate::region::RegionConfig;
@@ -92,90 +94,34 @@ fn score_lawn_biofuel(
     };
 
     // Reuse rtox corridor bands from RegionConfig.
-    let rtox = s.contaminant_rtox_01.max(0.0).min(1.0);
-    let rmicro = s.microplastics_r_01.max(0.0).min(1.0);
-    let rmetals = s.heavy_metals_r_01.max(0.0).min(1.0);
-    let rtrans = s.rtrans_01.max(0.0).min(1.0);
-    let rmat = s.rmat_01.max(0.0).min(1.0);
-    let rman = s.rman_01.max(0.0).min(1.0);
+    let rtox = s.contaminant_rtox_01.clamp(0.0, 1.0);
+    let rmicro = s.microplastics_r_01.clamp(0.0, 1.0);
+    let rmetals = s.heavy_metals_r_01.clamp(0.0, 1.0);
+    let rtrans = s.rtrans_01.clamp(0.0, 1.0);
+    let rmat = s.rmat_01.clamp(0.0, 1.0);
+    let rman = s.rman_01.clamp(0.0, 1.0);
 
     let rx = vec![
+        region.risk_coord("rt90", rt90),
         RiskCoord {
-            varid: "rt90".to_string(),
-            value: rt90,
-            safe: 0.3,
-            gold: 0.6,
-            hard: 1.0,
-            weight: 0.20,
-            lyapchannel: 40,
-        },
-        RiskCoord {
-            varid: "rtox".to_string(),
-            value: rtox,
             safe: region.rtox_safe(),
             gold: region.rtox_gold(),
             hard: region.rtox_hard(),
-            weight: 0.25,
-            lyapchannel: 41,
-        },
-        RiskCoord {
-            varid: "rmicro".to_string(),
-            value: rmicro,
-            safe: 0.1,
-            gold: 0.4,
-            hard: 1.0,
-            weight: 0.15,
-            lyapchannel: 42,
-        },
-        RiskCoord {
-            varid: "rmetals".to_string(),
-            value: rmetals,
-            safe: 0.1,
-            gold: 0.4,
-            hard: 1.0,
-            weight: 0.10,
-            lyapchannel: 43,
-        },
-        RiskCoord {
-            varid: "rtrans".to_string(),
-            value: rtrans,
-            safe: 0.3,
-            gold: 0.7,
-            hard: 1.0,
-            weight: 0.10,
-            lyapchannel: 44,
-        },
-        RiskCoord {
-            varid: "rmat".to_string(),
-            value: rmat,
-            safe: 0.3,
-            gold: 0.7,
-            hard: 1.0,
-            weight: 0.10,
-            lyapchannel: 45,
-        },
-        RiskCoord {
-            varid: "rman".to_string(),
-            value: rman,
-            safe: 0.3,
-            gold: 0.7,
-            hard: 1.0,
-            weight: 0.10,
-            lyapchannel: 46,
+            ..region.risk_coord("rtox", rtox)
         },
+        region.risk_coord("rmicro", rmicro),
+        region.risk_coord("rmetals", rmetals),
+        region.risk_coord("rtrans", rtrans),
+        region.risk_coord("rmat", rmat),
+        region.risk_coord("rman", rman),
     ];
 
-    let mut residual = Residual {
-        vt: 0.0,
-        weights: rx.iter().map(|r| r.weight).collect(),
-        rx,
-    };
-    residual.recompute();
+    let residual = Residual::new(rx);
 
     // Ecoimpact: high when we get good energy, low CO2eq, and low transport.
     // A simple, normalized placeholder:
     let e_energy = (s.bioenergy_kwh_per_kg / 3.0).min(1.0); // assume 3 kWh/kg ~ high
-    let e_carbon = (1.0 - (s.co2eq_kg_per_kg / 1.5)).max(0.0).min(1.0);
+    let e_carbon = (1.0 - (s.co2eq_kg_per_kg / 1.5)).clamp(0.0, 1.0);
     let e_transport = 1.0 - rtrans;
 
     let ecoimpact_raw = 0.4 * e_energy + 0.4 * e_carbon + 0.2 * e_transport;
@@ -1,5 +1,47 @@
+mod canonical;
+mod config;
 mod eco_core;
+mod energy_ledger;
 mod aln_anchor;
+mod aln_manifest;
+mod typewriter;
+mod attestation;
+mod fs_util;
+mod repo_state;
 
-pub use eco_core::{EnergySample, validate_repo_state};
-pub use aln_anchor::aln_serialize_commit;
+pub mod econet_core;
+pub mod region;
+pub mod hydromower;
+pub mod lawnbiofuel;
+
+pub use canonical::{
+    canonical_digest, canonical_timestamp, to_canonical_bytes, to_canonical_string,
+};
+pub use config::{
+    EcoSysConfig, EnergyOrchestration, TypewriterSettings, ComplianceSettings, RepositoryConfig,
+    ConfigError, DEFAULT_CONFIG_PATH,
+};
+pub use eco_core::EnergySample;
+pub use energy_ledger::{windows_signing_payload, EnergyLedger, EnergyWindow, LedgerEntry, LedgerTotals, LedgerError};
+pub use repo_state::{
+    validate_repo_state, compute_repo_digest, head_ref, repo_name, merkle_root, HeadRef, RepoValidation, RepoValidationStatus,
+    RepoStateError, TreeSource,
+};
+pub use aln_anchor::{
+    aln_serialize_commit, AnchorWriter, AnchorRecord, AnchorIndex, AnchorIndexEntry, AnchorError, JournalHead,
+    RecordCodec, DEFAULT_ANCHOR_DIR, ANCHOR_INDEX_FILE, HEX_BROTLI_ENCODING, RECORD_EXTENSION,
+};
+pub use aln_manifest::{
+    AlnManifest, ManifestMeta, ManifestAuthors, ManifestAnchors, ManifestSerialization,
+    ManifestEnergy, ManifestAudit, ManifestError, DEFAULT_MANIFEST_PATH,
+    SUPPORTED_HASH_FORMATS, SUPPORTED_ENCODINGS,
+};
+pub use attestation::{
+    SampleSigner, SampleAttestation, SignedEnergySample, AttestationError, TrustedSigner, key_id_for,
+    DEFAULT_SIGNING_KEY_PATH,
+};
+pub use typewriter::{
+    TypewriterJournal, JournalRecord, JournalCommit, JournalEnergySample, JournalError,
+    ProvenanceEntry, ChainFault, ChainReport, verify_chain, verify_chain_anchors, verify_journal,
+    DEFAULT_JOURNAL_PATH,
+};
@@ -1,38 +1,352 @@
-use eco_sys::{EnergySample, validate_repo_state, aln_serialize_commit};
-use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
-fn main() {
-    println!("═══════════════════════════════════════════");
-    println!("  ECO‑SYS: Environmental Orchestration Layer");
-    println!("  Author: Doctor0Evil");
-    println!("  DID: bostrom18sd2ujv24ual9c9pshtxys6j8knh6xaead9ye7");
-    println!("═══════════════════════════════════════════\n");
+use chrono::Utc;
+use clap::{Args, Parser, Subcommand};
+use eco_sys::{
+    compute_repo_digest, head_ref, repo_name, validate_repo_state, verify_journal, AlnManifest,
+    AnchorRecord, AnchorWriter, EcoSysConfig, EnergyLedger, EnergySample, JournalCommit, SampleSigner, SignedEnergySample,
+    TreeSource, TrustedSigner, TypewriterJournal, DEFAULT_CONFIG_PATH,
+};
+
+/// Eco-Sys CLI entrypoint.
+/// - Loads `virta-git.config.json` (energy_orchestration, typewriter_settings, compliance)
+/// - Signs energy samples, anchors them to ALN and appends them to the Typewriter journal
+/// - Verifies the journal, anchors and repository state offline
+#[derive(Parser, Debug)]
+#[command(name = "virta-git")]
+#[command(version)]
+#[command(about = "Eco-Sys: environmental compliance and energy-aware orchestration layer.")]
+struct Cli {
+    /// Path to virta-git.config.json
+    #[arg(long, default_value = DEFAULT_CONFIG_PATH)]
+    config: PathBuf,
+
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Compute and sign an energy sample without recording it.
+    Sample(SampleArgs),
+
+    /// Sign a sample, validate the repository, write an ALN anchor and append it to the journal.
+    Anchor {
+        #[command(flatten)]
+        sample: SampleArgs,
+
+        /// Repository to validate (worktree against HEAD) and anchor
+        #[arg(long, default_value = ".")]
+        repo: PathBuf,
+
+        /// JSONL file of timestamped per-machine samples to integrate into the journal entry
+        #[arg(long)]
+        ledger: Option<PathBuf>,
+
+        /// Ledger integration window in seconds
+        #[arg(long, default_value = "3600")]
+        window_seconds: i64,
+    },
+
+    /// Verify the journal's provenance chain and signatures, every indexed anchor,
+    /// and the repository worktree against the digest stored in the latest anchor.
+    Verify {
+        /// Journal to verify (defaults to typewriter_settings.journal_path)
+        #[arg(long)]
+        journal: Option<PathBuf>,
+
+        /// Repository to validate
+        #[arg(long, default_value = ".")]
+        repo: PathBuf,
+
+        /// Expected Merkle SHA-512 of the worktree (defaults to the digest stored in the latest anchor)
+        #[arg(long)]
+        proof: Option<String>,
+    },
+
+    /// Show the most recent Typewriter journal entries.
+    Journal {
+        /// Number of commit entries to show
+        #[arg(long, default_value = "5")]
+        tail: usize,
+    },
+}
+
+/// Sample inputs; each defaults to the config's `energy_orchestration` section.
+#[derive(Args, Debug)]
+struct SampleArgs {
+    #[arg(long)]
+    baseline_x_mwz: Option<f64>,
+
+    #[arg(long)]
+    baseline_y_mwz: Option<f64>,
+
+    /// Target utilization (0.0 - 1.0)
+    #[arg(long)]
+    target_utilization: Option<f64>,
+
+    /// PKCS#8 Ed25519 key (defaults to typewriter_settings.signing_key_path)
+    #[arg(long)]
+    key: Option<PathBuf>,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let config = match EcoSysConfig::load(&cli.config) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("✗ {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let result = match &cli.command {
+        Commands::Sample(args) => {
+            load_signer(&config, args.key.as_deref()).and_then(|signer| run_sample(&config, args, &signer)).map(|_| true)
+        }
+        Commands::Anchor { sample, repo, ledger, window_seconds } => {
+            let ledger = ledger.as_deref().map(|path| (path, *window_seconds));
+            run_anchor(&config, sample, repo, ledger)
+        }
+        Commands::Verify { journal, repo, proof } => run_verify(&config, journal.as_deref(), repo, proof.as_deref()),
+        Commands::Journal { tail } => run_journal(&config, *tail).map(|_| true),
+    };
 
-    let config_data = fs::read_to_string("virta-git.config.json")
-        .expect("Failed to read configuration file");
-    println!("✓ Configuration loaded successfully\n");
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("✗ {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+type CliResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+fn load_signer(config: &EcoSysConfig, key: Option<&Path>) -> CliResult<SampleSigner> {
+    let did = &config.typewriter_settings.authorship_did;
+    let key_path = key.map(Path::to_path_buf).unwrap_or_else(|| config.signing_key_path());
+    if key_path.exists() {
+        Ok(SampleSigner::load_pkcs8(&key_path, did)?)
+    } else {
+        println!("! No signing key at {}, generating a new Ed25519 keypair", key_path.display());
+        Ok(SampleSigner::generate_pkcs8(&key_path, did)?)
+    }
+}
+
+/// The author DID and pinned public keys from the manifest; journal and
+/// anchor signatures are only trusted against these.
+fn trusted_signer(config: &EcoSysConfig) -> CliResult<TrustedSigner> {
+    Ok(AlnManifest::load(config.manifest_path())?.authors.trusted_signer())
+}
 
-    let sample = EnergySample::new(1200.0, 900.0, 0.7);
-    let efficiency = sample.compute_efficiency();
-    let signature = sample.sign_sample();
+fn run_sample(config: &EcoSysConfig, args: &SampleArgs, signer: &SampleSigner) -> CliResult<SignedEnergySample> {
+    let energy = &config.energy_orchestration;
+    let signed = signer.sign(EnergySample::new(
+        args.baseline_x_mwz.unwrap_or(energy.baseline_x_mwz),
+        args.baseline_y_mwz.unwrap_or(energy.baseline_y_mwz),
+        args.target_utilization.unwrap_or(energy.target_utilization),
+    ));
+    let sample = &signed.sample;
 
     println!("Energy Sample:");
+    println!("  Machines: {}", energy.total_machines);
     println!("  Baseline X: {} MWz", sample.baseline_x_mwz);
     println!("  Baseline Y: {} MWz", sample.baseline_y_mwz);
     println!("  Target Utilization: {:.1}%", sample.target_utilization * 100.0);
-    println!("  Computed Efficiency: {:.2} MWz", efficiency);
-    println!("  Signature (SHA-512): {}\n", &signature[0..64]);
+    println!("  Computed Efficiency: {:.2} MWz", sample.compute_efficiency());
+    println!("  Digest (SHA-512): {}", &sample.sign_sample()[0..64]);
+    println!("  Signature (Ed25519): {}", signed.attestation.signature);
+    println!("  Key ID: {}\n", signed.attestation.key_id);
+    Ok(signed)
+}
+
+fn run_anchor(
+    config: &EcoSysConfig,
+    args: &SampleArgs,
+    repo: &Path,
+    ledger: Option<(&Path, i64)>,
+) -> CliResult<bool> {
+    println!("═══════════════════════════════════════════");
+    println!("  ECO‑SYS: Environmental Orchestration Layer");
+    println!("  DID: {}", config.typewriter_settings.authorship_did);
+    println!("═══════════════════════════════════════════\n");
+
+    let manifest_path = config.manifest_path();
+    let manifest = AlnManifest::load(&manifest_path)?;
+    manifest.validate()?;
+    let trusted = manifest.authors.trusted_signer();
 
-    let repo_valid = validate_repo_state("Eco-Sys", &signature);
-    println!("Repository Validation: {}\n", if repo_valid { "✓ PASSED" } else { "✗ FAILED" });
+    let journal_path = config.journal_path();
+    let mut journal = TypewriterJournal::load(&journal_path)?;
+    if config.compliance.tamper_evident {
+        let report = journal.verify(&trusted, Some(&AnchorWriter::new(config.anchor_dir())))?;
+        if let Some(fault) = report.fault {
+            println!("✗ Refusing to append to tampered journal {}: {}", journal_path.display(), fault);
+            return Ok(false);
+        }
+    }
 
-    let aln_hash = aln_serialize_commit(
-        "main@eco-sys-v1.0.0",
-        &signature,
-        "bostrom18sd2ujv24ual9c9pshtxys6j8knh6xaead9ye7"
+    let signer = load_signer(config, args.key.as_deref())?;
+    let signed = run_sample(config, args, &signer)?;
+    if let Err(e) = signed.verify(&trusted) {
+        println!("✗ Refusing to anchor: {e}");
+        println!(
+            "  Pin the signing key by adding public_keys = [\"{}\"] under [authors] in {}",
+            signed.attestation.public_key,
+            manifest_path.display(),
+        );
+        return Ok(false);
+    }
+
+    // Only a worktree that reproduces the committed HEAD tree is anchored; its
+    // digest is stored in the anchor as the proof later validations check.
+    let exclude = config.generated_paths();
+    let (head_proof, _) = compute_repo_digest(repo, TreeSource::Head, &exclude)?;
+    let validation = validate_repo_state(repo, &head_proof, TreeSource::Worktree, &exclude)?;
+    if !validation.passed() {
+        println!("✗ Refusing to anchor: worktree differs from HEAD ({} files)", validation.file_count);
+        return Ok(false);
+    }
+    println!(
+        "Repository Validation: ✓ PASSED ({} files, Merkle SHA-512 {})\n",
+        validation.file_count,
+        &validation.computed_digest[0..16],
     );
-    println!("ALN Anchor Hash: {}\n", aln_hash);
+    let head = head_ref(repo)?;
+    let repo_state = head.to_string();
+    let repo_state = repo_state.as_str();
+
+    // Everything that can fail on bad input runs before anything is written.
+    let mut commit = JournalCommit::from_signed(repo_state, &repo_name(repo)?, &head.branch, &signed, &trusted)?;
+    if let Some((path, window_seconds)) = ledger {
+        let ledger = EnergyLedger::load_jsonl(path, window_seconds)?;
+        let totals = ledger.totals();
+        println!(
+            "Energy Ledger: {} machines, {} windows, {:.2} MWz·h effective of {:.2} MWz·h baseline (peak {:.2} MWz)\n",
+            totals.machines,
+            totals.windows,
+            totals.effective_energy_mwzh,
+            totals.baseline_energy_mwzh,
+            totals.peak_mwz,
+        );
+        commit = commit.with_energy_windows(ledger.windows(), totals, &signer);
+    }
+
+    // Pin the chain as it stands so a later cut or rewrite shows up against this anchor.
+    let record = AnchorRecord::new(
+        repo_state,
+        &signed.sample.sign_sample(),
+        &config.typewriter_settings.authorship_did,
+        Some(&signed.attestation),
+        Utc::now(),
+    )
+    .with_journal_head(journal.chain_head()?)
+    .with_repo_digest(&head_proof);
+    let aln_hash = record.anchor_hash()?;
+    journal.append_commit(commit);
+    let ledger_node = manifest.audit.as_ref().map_or("Googolswarm.os-node-42", |a| a.ledger_node.as_str());
+    let proof_format = manifest.audit.as_ref().map_or("ALN-MultiSig-v2", |a| a.proof_format.as_str());
+    journal.append_provenance(&config.compliance.blockchain_anchor, ledger_node, &aln_hash, proof_format)?;
+
+    // Only indexed anchors are checked against the journal, so the anchor is
+    // indexed after the journal that records it is saved. A failure in between
+    // leaves an unindexed anchor file, which is not a chain fault, and the next
+    // run can anchor again.
+    let anchor_writer = AnchorWriter::new(config.anchor_dir()).with_manifest(&manifest_path);
+    anchor_writer.write_unindexed(&record)?;
+    journal.save()?;
+    anchor_writer.register(&record)?;
+    println!("ALN Anchor Hash: {}", aln_hash);
+    println!("ALN Anchor File: {}\n", anchor_writer.anchor_path(&aln_hash)?.display());
+    println!("✓ Typewriter journal written to {}", journal.path().display());
+    Ok(true)
+}
+
+fn run_verify(
+    config: &EcoSysConfig,
+    journal: Option<&Path>,
+    repo: &Path,
+    proof: Option<&str>,
+) -> CliResult<bool> {
+    let mut ok = true;
+
+    let journal_path = journal.map(Path::to_path_buf).unwrap_or_else(|| config.journal_path());
+    let anchor_writer = AnchorWriter::new(config.anchor_dir());
+    let report = verify_journal(&journal_path, &trusted_signer(config)?, Some(&anchor_writer))?;
+    match report.fault {
+        None => println!("✓ Provenance chain intact ({} entries): {}", report.entries, journal_path.display()),
+        Some(fault) => {
+            println!("✗ Provenance chain broken in {}: {}", journal_path.display(), fault);
+            ok = false;
+        }
+    }
+
+    let index = anchor_writer.index()?;
+    let bad: Vec<_> = index
+        .anchors
+        .iter()
+        .filter_map(|entry| anchor_writer.read(&entry.anchor_hash).err().map(|e| (entry, e)))
+        .collect();
+    for (entry, e) in &bad {
+        println!("✗ Anchor {}: {}", entry.file, e);
+    }
+    if bad.is_empty() {
+        println!("✓ {} anchors match their hashes in {}", index.anchors.len(), anchor_writer.dir().display());
+    }
+    ok &= bad.is_empty();
+
+    let expected = match proof {
+        Some(p) => p.to_string(),
+        None => match anchor_writer.latest_repo_digest()? {
+            Some(digest) => digest,
+            None => {
+                println!("✗ No anchor stores a repository proof; anchor first or pass --proof");
+                return Ok(false);
+            }
+        },
+    };
+    let validation = validate_repo_state(repo, &expected, TreeSource::Worktree, &config.generated_paths())?;
+    if validation.passed() {
+        println!("✓ Repository state matches proof ({} files)", validation.file_count);
+    } else {
+        println!(
+            "✗ Repository state mismatch: computed {}, expected {}",
+            validation.computed_digest, validation.expected_digest
+        );
+        ok = false;
+    }
+
+    Ok(ok)
+}
+
+fn run_journal(config: &EcoSysConfig, tail: usize) -> CliResult<()> {
+    let journal = TypewriterJournal::load(config.journal_path())?;
+    let trusted = trusted_signer(config)?;
+    let record = journal.record();
+    println!("Typewriter journal {} (v{})", journal.path().display(), record.journal_version);
+    println!("  Author: {} ({})", record.author.name, record.author.did);
+    println!("  Commits: {}  Provenance entries: {}\n", record.commits.len(), record.provenance_chain.len());
 
-    println!("✓ Typewriter journal written to /data-lake/eco-sys/");
-    println!("✓ All operations completed successfully");
+    let skip = record.commits.len().saturating_sub(tail);
+    for commit in record.commits.iter().skip(skip) {
+        let signed = match commit.verify_attestation(&trusted) {
+            Ok(true) => "signed ✓",
+            Ok(false) => "unsigned",
+            Err(_) => "signature ✗",
+        };
+        println!(
+            "  {} {} {}@{} efficiency={:.2} MWz [{}]",
+            commit.timestamp,
+            commit.commit_id,
+            commit.repo,
+            commit.branch,
+            commit.energy_sample.computed_efficiency_mwz,
+            signed,
+        );
+    }
+    Ok(())
 }
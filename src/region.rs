@@ -0,0 +1,410 @@
+//! Region-specific corridor bands used by the shard producers.
+//!
+//! A region is a TOML file (`config/regions/<id>.toml`) holding physical ramps and
+//! biodegradation limits, plus a CSV of normalized risk-coordinate bands that it
+//! names in `region.bands_csv`. Adding a region means adding those two files.
+
+use serde::{Serialize, Deserialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::econet_core::{normalize, Direction, RiskCoord};
+
+pub const DEFAULT_REGION_DIR: &str = "config/regions";
+
+/// Risk coordinates the hydromower and lawnbiofuel producers read from a region.
+pub const REQUIRED_BANDS: &[&str] = &[
+    "rcutquality", "rwateruse", "rnoise", "rspraydrift", "rsafety",
+    "rt90", "rtox", "rmicro", "rmetals", "rtrans", "rmat", "rman",
+];
+
+#[derive(Debug, Error)]
+pub enum RegionError {
+    #[error("io error on {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("malformed region file {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+    #[error("malformed band file {path} line {line}: {message}")]
+    Csv { path: PathBuf, line: usize, message: String },
+    #[error("region {region} has no band for {varid}")]
+    MissingBand { region: String, varid: String },
+    #[error("region {region}: invalid {name}: {reason}")]
+    Invalid { region: String, name: String, reason: String },
+    #[error("duplicate region id {0}")]
+    DuplicateRegion(String),
+}
+
+/// Linear ramp from a physical reading to a risk coordinate: 0 at `safe`, 1 at `hard`.
+/// `hard` below `safe` describes a parameter that is safe when high.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Ramp {
+    pub safe: f64,
+    pub hard: f64,
+}
+
+impl Ramp {
+    pub fn risk(&self, x: f64) -> f64 {
+        normalize(x, self.safe, self.hard, Direction::Max)
+    }
+}
+
+/// Normalized bands, weight and Lyapunov channel of one risk coordinate.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct CoordBand {
+    pub safe: f64,
+    pub gold: f64,
+    pub hard: f64,
+    pub weight: f64,
+    pub lyapchannel: u32,
+}
+
+impl CoordBand {
+    /// Zero-width hard band for a coordinate a region does not define, so any
+    /// reading of it is a hard violation.
+    pub const UNDEFINED: CoordBand = CoordBand { safe: 0.0, gold: 0.0, hard: 0.0, weight: 1.0, lyapchannel: 0 };
+
+    pub fn coord(&self, varid: &str, value: f64) -> RiskCoord {
+        RiskCoord {
+            varid: varid.to_string(),
+            value,
+            safe: self.safe,
+            gold: self.gold,
+            hard: self.hard,
+            weight: self.weight,
+            lyapchannel: self.lyapchannel,
+        }
+    }
+}
+
+/// Corridor bands for one deployment region.
+pub trait RegionConfig {
+    fn region_id(&self) -> &str;
+
+    /// Biodegradation t90 at or below which `rt90` is 0.
+    fn t90target_days(&self) -> f64;
+    /// Biodegradation t90 at or above which `rt90` is 1.
+    fn t90hardlimit_days(&self) -> f64;
+
+    fn rtox_safe(&self) -> f64;
+    fn rtox_gold(&self) -> f64;
+    fn rtox_hard(&self) -> f64;
+
+    /// Water use in L/m² per cycle.
+    fn wateruse_ramp(&self) -> Ramp;
+    /// Operating noise in dBA.
+    fn noise_ramp(&self) -> Ramp;
+    /// Spray drift radius in metres.
+    fn spraydrift_ramp(&self) -> Ramp;
+    /// Nozzle pressure at or below which jets count as contained.
+    fn nozzle_pressure_safe_bar(&self) -> f64;
+
+    fn coord_band(&self, varid: &str) -> Option<CoordBand>;
+
+    /// Risk coordinate for `varid` with this region's bands. A coordinate the
+    /// region has no band for gets a zero-width hard band, so the step stops.
+    fn risk_coord(&self, varid: &str, value: f64) -> RiskCoord {
+        self.coord_band(varid).unwrap_or(CoordBand::UNDEFINED).coord(varid, value)
+    }
+}
+
+#[derive(Deserialize)]
+struct RegionFile {
+    region: RegionMeta,
+    biodegradation: Biodegradation,
+    hydromower: HydroMowerRamps,
+}
+
+#[derive(Deserialize)]
+struct RegionMeta {
+    id: String,
+    #[serde(default)]
+    name: String,
+    bands_csv: String,
+}
+
+#[derive(Deserialize)]
+struct Biodegradation {
+    t90target_days: f64,
+    t90hardlimit_days: f64,
+}
+
+#[derive(Deserialize)]
+struct HydroMowerRamps {
+    nozzle_pressure_safe_bar: f64,
+    wateruse_l_per_m2: Ramp,
+    noise_dba: Ramp,
+    spray_drift_m: Ramp,
+}
+
+/// `RegionConfig` backed by a region TOML file and its band CSV.
+///
+/// Bands are private so a config can only come out of `load`, which checks
+/// that every `REQUIRED_BANDS` entry is present.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileRegionConfig {
+    pub id: String,
+    pub name: String,
+    pub t90target_days: f64,
+    pub t90hardlimit_days: f64,
+    pub nozzle_pressure_safe_bar: f64,
+    pub wateruse: Ramp,
+    pub noise: Ramp,
+    pub spraydrift: Ramp,
+    bands: BTreeMap<String, CoordBand>,
+}
+
+impl FileRegionConfig {
+    /// Load a region TOML file; `bands_csv` is resolved next to it.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RegionError> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path).map_err(|source| RegionError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let file: RegionFile = toml::from_str(&data).map_err(|source| RegionError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let bands_path = path.parent().unwrap_or(Path::new("")).join(&file.region.bands_csv);
+        let region = FileRegionConfig {
+            id: file.region.id,
+            name: file.region.name,
+            t90target_days: file.biodegradation.t90target_days,
+            t90hardlimit_days: file.biodegradation.t90hardlimit_days,
+            nozzle_pressure_safe_bar: file.hydromower.nozzle_pressure_safe_bar,
+            wateruse: file.hydromower.wateruse_l_per_m2,
+            noise: file.hydromower.noise_dba,
+            spraydrift: file.hydromower.spray_drift_m,
+            bands: load_bands(&bands_path)?,
+        };
+        region.validate()?;
+        Ok(region)
+    }
+
+    pub fn bands(&self) -> &BTreeMap<String, CoordBand> {
+        &self.bands
+    }
+
+    fn invalid(&self, name: &str, reason: String) -> RegionError {
+        RegionError::Invalid { region: self.id.clone(), name: name.to_string(), reason }
+    }
+
+    /// Reject missing bands, unordered thresholds and zero-width ramps.
+    pub fn validate(&self) -> Result<(), RegionError> {
+        for varid in REQUIRED_BANDS {
+            if !self.bands.contains_key(*varid) {
+                return Err(RegionError::MissingBand { region: self.id.clone(), varid: varid.to_string() });
+            }
+        }
+        for (varid, b) in &self.bands {
+            if !(0.0 <= b.safe && b.safe <= b.gold && b.gold <= b.hard && b.hard <= 1.0) {
+                return Err(self.invalid(varid, format!("need 0 <= safe <= gold <= hard <= 1, got {b:?}")));
+            }
+            if !b.weight.is_finite() || b.weight < 0.0 {
+                return Err(self.invalid(varid, format!("weight {} must be finite and >= 0", b.weight)));
+            }
+        }
+        if self.t90target_days.partial_cmp(&self.t90hardlimit_days) != Some(Ordering::Less) {
+            return Err(self.invalid(
+                "t90",
+                format!("target {} must be below hard limit {}", self.t90target_days, self.t90hardlimit_days),
+            ));
+        }
+        for (name, ramp) in [("wateruse_l_per_m2", self.wateruse), ("noise_dba", self.noise), ("spray_drift_m", self.spraydrift)] {
+            if !(ramp.hard - ramp.safe).is_normal() {
+                return Err(self.invalid(name, format!("safe {} and hard {} must differ", ramp.safe, ramp.hard)));
+            }
+        }
+        Ok(())
+    }
+
+    fn band(&self, varid: &str) -> CoordBand {
+        // Present for every REQUIRED_BANDS entry once `load` has validated;
+        // fail closed rather than panic if that ever stops holding.
+        self.coord_band(varid).unwrap_or(CoordBand::UNDEFINED)
+    }
+}
+
+fn load_bands(path: &Path) -> Result<BTreeMap<String, CoordBand>, RegionError> {
+    let data = fs::read_to_string(path).map_err(|source| RegionError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let csv_err = |line: usize, message: String| RegionError::Csv { path: path.to_path_buf(), line, message };
+
+    let mut lines = data.lines().enumerate().filter(|(_, l)| !l.trim().is_empty() && !l.starts_with('#'));
+    let (_, header) = lines.next().ok_or_else(|| csv_err(1, "missing header".into()))?;
+    let header: Vec<&str> = header.split(',').map(str::trim).collect();
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|h| *h == name)
+            .ok_or_else(|| csv_err(1, format!("missing column {name:?}")))
+    };
+    let cols = [column("varid")?, column("safe")?, column("gold")?, column("hard")?, column("weight")?, column("lyapchannel")?];
+
+    let mut bands = BTreeMap::new();
+    for (i, line) in lines {
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let field = |c: usize| fields.get(cols[c]).copied().ok_or_else(|| csv_err(i + 1, "too few fields".into()));
+        let number = |c: usize| -> Result<f64, RegionError> {
+            let raw = field(c)?;
+            raw.parse().map_err(|_| csv_err(i + 1, format!("{:?} is not a number", raw)))
+        };
+        let varid = field(0)?.to_string();
+        let lyapchannel = field(5)?;
+        let band = CoordBand {
+            safe: number(1)?,
+            gold: number(2)?,
+            hard: number(3)?,
+            weight: number(4)?,
+            lyapchannel: lyapchannel
+                .parse()
+                .map_err(|_| csv_err(i + 1, format!("{:?} is not a channel index", lyapchannel)))?,
+        };
+        if bands.insert(varid.clone(), band).is_some() {
+            return Err(csv_err(i + 1, format!("duplicate varid {varid:?}")));
+        }
+    }
+    Ok(bands)
+}
+
+impl RegionConfig for FileRegionConfig {
+    fn region_id(&self) -> &str {
+        &self.id
+    }
+
+    fn t90target_days(&self) -> f64 {
+        self.t90target_days
+    }
+
+    fn t90hardlimit_days(&self) -> f64 {
+        self.t90hardlimit_days
+    }
+
+    fn rtox_safe(&self) -> f64 {
+        self.band("rtox").safe
+    }
+
+    fn rtox_gold(&self) -> f64 {
+        self.band("rtox").gold
+    }
+
+    fn rtox_hard(&self) -> f64 {
+        self.band("rtox").hard
+    }
+
+    fn wateruse_ramp(&self) -> Ramp {
+        self.wateruse
+    }
+
+    fn noise_ramp(&self) -> Ramp {
+        self.noise
+    }
+
+    fn spraydrift_ramp(&self) -> Ramp {
+        self.spraydrift
+    }
+
+    fn nozzle_pressure_safe_bar(&self) -> f64 {
+        self.nozzle_pressure_safe_bar
+    }
+
+    fn coord_band(&self, varid: &str) -> Option<CoordBand> {
+        self.bands.get(varid).copied()
+    }
+}
+
+/// All regions found in a directory, keyed by region id.
+#[derive(Debug, Clone, Default)]
+pub struct RegionRegistry {
+    regions: BTreeMap<String, FileRegionConfig>,
+}
+
+impl RegionRegistry {
+    /// Load every `*.toml` region file in `dir`.
+    pub fn load_dir(dir: impl AsRef<Path>) -> Result<Self, RegionError> {
+        let dir = dir.as_ref();
+        let io_err = |source| RegionError::Io { path: dir.to_path_buf(), source };
+        let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+            .map_err(io_err)?
+            .map(|e| e.map(|e| e.path()))
+            .collect::<Result<_, _>>()
+            .map_err(io_err)?;
+        paths.retain(|p| p.extension().is_some_and(|e| e == "toml"));
+        paths.sort();
+
+        let mut registry = RegionRegistry::default();
+        for path in paths {
+            let region = FileRegionConfig::load(&path)?;
+            if registry.regions.contains_key(&region.id) {
+                return Err(RegionError::DuplicateRegion(region.id));
+            }
+            registry.regions.insert(region.id.clone(), region);
+        }
+        Ok(registry)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&FileRegionConfig> {
+        self.regions.get(id)
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.regions.keys().map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repo_regions_load() {
+        let registry = RegionRegistry::load_dir(DEFAULT_REGION_DIR).unwrap();
+        assert_eq!(registry.ids().collect::<Vec<_>>(), vec!["Phoenix-AZ-US", "Tempe-AZ-US"]);
+
+        let phx = registry.get("Phoenix-AZ-US").unwrap();
+        assert_eq!(phx.wateruse_ramp(), Ramp { safe: 2.0, hard: 8.0 });
+        assert_eq!(phx.noise_ramp().risk(80.0), 0.5);
+        assert_eq!((phx.rtox_safe(), phx.rtox_gold(), phx.rtox_hard()), (0.1, 0.4, 1.0));
+        assert_eq!(phx.risk_coord("rnoise", 0.4).lyapchannel, 32);
+
+        let tempe = registry.get("Tempe-AZ-US").unwrap();
+        assert!(tempe.noise_ramp().safe < phx.noise_ramp().safe);
+        assert!(tempe.t90hardlimit_days() < phx.t90hardlimit_days());
+    }
+
+    #[test]
+    fn test_missing_band_and_unknown_varid() {
+        let dir = tempfile::tempdir().unwrap();
+        let toml = fs::read_to_string(Path::new(DEFAULT_REGION_DIR).join("Phoenix-AZ-US.toml")).unwrap();
+        fs::write(dir.path().join("r.toml"), toml).unwrap();
+        let bands = fs::read_to_string(Path::new(DEFAULT_REGION_DIR).join("Phoenix-AZ-US.bands.csv")).unwrap();
+        let without_rman: String = bands.lines().filter(|l| !l.starts_with("rman")).map(|l| format!("{l}\n")).collect();
+        fs::write(dir.path().join("Phoenix-AZ-US.bands.csv"), without_rman).unwrap();
+        assert!(matches!(
+            FileRegionConfig::load(dir.path().join("r.toml")),
+            Err(RegionError::MissingBand { varid, .. }) if varid == "rman"
+        ));
+
+        fs::write(dir.path().join("Phoenix-AZ-US.bands.csv"), bands.replace("rman,0.3,0.7", "rman,0.8,0.7")).unwrap();
+        assert!(matches!(FileRegionConfig::load(dir.path().join("r.toml")), Err(RegionError::Invalid { .. })));
+
+        let mut phx = FileRegionConfig::load(Path::new(DEFAULT_REGION_DIR).join("Phoenix-AZ-US.toml")).unwrap();
+        assert!(phx.risk_coord("rundocumented", 0.0).hard_violation());
+
+        phx.bands.remove("rtox");
+        assert_eq!((phx.rtox_safe(), phx.rtox_gold(), phx.rtox_hard()), (0.0, 0.0, 0.0));
+    }
+}
@@ -0,0 +1,361 @@
+use ring::digest::{digest, Context, SHA512};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum RepoStateError {
+    #[error("failed to run git {args}: {source}")]
+    Spawn {
+        args: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("git {args} failed: {stderr}")]
+    Git { args: String, stderr: String },
+    #[error("io error on {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("unexpected git output: {0}")]
+    Protocol(String),
+}
+
+/// Which view of the repository is digested.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeSource {
+    /// Files tracked by the index, read from the working tree.
+    Worktree,
+    /// Blobs of the tree committed at HEAD.
+    Head,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepoValidationStatus {
+    Match,
+    Mismatch,
+}
+
+/// Outcome of comparing a repository's Merkle digest against a stored proof.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepoValidation {
+    pub repo_path: PathBuf,
+    pub source: TreeSource,
+    pub file_count: usize,
+    pub computed_digest: String,
+    pub expected_digest: String,
+    pub status: RepoValidationStatus,
+}
+
+impl RepoValidation {
+    pub fn passed(&self) -> bool {
+        self.status == RepoValidationStatus::Match
+    }
+}
+
+/// Branch and commit checked out at HEAD; `branch` is `HEAD` when detached.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeadRef {
+    pub branch: String,
+    pub commit: String,
+}
+
+impl std::fmt::Display for HeadRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}@{}", self.branch, self.commit)
+    }
+}
+
+/// Read the checked-out branch and commit of `repo` from git.
+pub fn head_ref(repo: &Path) -> Result<HeadRef, RepoStateError> {
+    let read = |args: &[&str]| git(repo, args).map(|out| String::from_utf8_lossy(&out).trim().to_string());
+    Ok(HeadRef {
+        branch: read(&["rev-parse", "--abbrev-ref", "HEAD"])?,
+        commit: read(&["rev-parse", "HEAD"])?,
+    })
+}
+
+/// Repository name: the last segment of the `origin` remote URL without
+/// `.git`, or the worktree directory name when there is no origin.
+pub fn repo_name(repo: &Path) -> Result<String, RepoStateError> {
+    let origin = git(repo, &["config", "--get", "remote.origin.url"]).ok();
+    let source = match origin {
+        Some(url) => String::from_utf8_lossy(&url).trim().to_string(),
+        None => String::from_utf8_lossy(&git(repo, &["rev-parse", "--show-toplevel"])?).trim().to_string(),
+    };
+    source
+        .trim_end_matches('/')
+        .rsplit(['/', ':'])
+        .next()
+        .map(|name| name.trim_end_matches(".git").to_string())
+        .filter(|name| !name.is_empty())
+        .ok_or(RepoStateError::Protocol(source))
+}
+
+/// Merkle root over `(path, content)` leaves, sorted by path.
+///
+/// leaf = SHA-512(0x00 || path || 0x00 || SHA-512(content)),
+/// node = SHA-512(0x01 || left || right); an odd node is carried up unchanged.
+/// An empty tree digests to SHA-512 of the empty string.
+pub fn merkle_root(mut leaves: Vec<(String, Vec<u8>)>) -> String {
+    leaves.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut level: Vec<Vec<u8>> = leaves
+        .iter()
+        .map(|(path, content)| {
+            let mut ctx = Context::new(&SHA512);
+            ctx.update(&[0x00]);
+            ctx.update(path.as_bytes());
+            ctx.update(&[0x00]);
+            ctx.update(digest(&SHA512, content).as_ref());
+            ctx.finish().as_ref().to_vec()
+        })
+        .collect();
+    if level.is_empty() {
+        return hex::encode(digest(&SHA512, b""));
+    }
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => {
+                    let mut ctx = Context::new(&SHA512);
+                    ctx.update(&[0x01]);
+                    ctx.update(left);
+                    ctx.update(right);
+                    ctx.finish().as_ref().to_vec()
+                }
+                [single] => single.clone(),
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    hex::encode(&level[0])
+}
+
+/// Compute the Merkle digest of a repository and the number of files it covers.
+///
+/// Files under any repository-relative path in `exclude` are left out, so
+/// outputs the anchoring itself rewrites (journal, manifest, anchors) do not
+/// invalidate the digest they are recorded with.
+pub fn compute_repo_digest(
+    repo: &Path,
+    source: TreeSource,
+    exclude: &[PathBuf],
+) -> Result<(String, usize), RepoStateError> {
+    let mut leaves = match source {
+        TreeSource::Worktree => worktree_leaves(repo)?,
+        TreeSource::Head => head_leaves(repo)?,
+    };
+    leaves.retain(|(path, _)| !exclude.iter().any(|ex| Path::new(path).starts_with(ex)));
+    let count = leaves.len();
+    Ok((merkle_root(leaves), count))
+}
+
+/// Digest the repository at `repo` and compare it in full against `expected_proof`
+/// (hex, optional `0x` prefix, case-insensitive).
+pub fn validate_repo_state(
+    repo: &Path,
+    expected_proof: &str,
+    source: TreeSource,
+    exclude: &[PathBuf],
+) -> Result<RepoValidation, RepoStateError> {
+    let (computed_digest, file_count) = compute_repo_digest(repo, source, exclude)?;
+    let expected_digest = expected_proof.trim().trim_start_matches("0x").to_ascii_lowercase();
+    let status = if computed_digest == expected_digest {
+        RepoValidationStatus::Match
+    } else {
+        RepoValidationStatus::Mismatch
+    };
+    Ok(RepoValidation {
+        repo_path: repo.to_path_buf(),
+        source,
+        file_count,
+        computed_digest,
+        expected_digest,
+        status,
+    })
+}
+
+fn git(repo: &Path, args: &[&str]) -> Result<Vec<u8>, RepoStateError> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .map_err(|source| RepoStateError::Spawn { args: args.join(" "), source })?;
+    if !output.status.success() {
+        return Err(RepoStateError::Git {
+            args: args.join(" "),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    Ok(output.stdout)
+}
+
+fn split_nul(out: &[u8]) -> impl Iterator<Item = &[u8]> {
+    out.split(|b| *b == 0).filter(|s| !s.is_empty())
+}
+
+fn worktree_leaves(repo: &Path) -> Result<Vec<(String, Vec<u8>)>, RepoStateError> {
+    let listing = git(repo, &["ls-files", "-z", "--cached"])?;
+    let mut leaves = Vec::new();
+    for raw in split_nul(&listing) {
+        let rel = String::from_utf8_lossy(raw).into_owned();
+        let path = repo.join(&rel);
+        let meta = match fs::symlink_metadata(&path) {
+            Ok(meta) => meta,
+            // Tracked but deleted from the worktree: leave it out so the digest differs from HEAD.
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(source) => return Err(RepoStateError::Io { path, source }),
+        };
+        // Symlinks are stored in git as their target path; submodules are not blobs.
+        let content = if meta.file_type().is_symlink() {
+            fs::read_link(&path).map(|t| t.to_string_lossy().into_owned().into_bytes())
+        } else if meta.is_dir() {
+            continue;
+        } else {
+            fs::read(&path)
+        };
+        let content = content.map_err(|source| RepoStateError::Io { path, source })?;
+        leaves.push((rel, content));
+    }
+    Ok(leaves)
+}
+
+fn head_leaves(repo: &Path) -> Result<Vec<(String, Vec<u8>)>, RepoStateError> {
+    let listing = git(repo, &["ls-tree", "-r", "-z", "HEAD"])?;
+    let mut entries = Vec::new();
+    for raw in split_nul(&listing) {
+        // "<mode> <type> <oid>\t<path>"
+        let line = String::from_utf8_lossy(raw);
+        let (meta, path) = line
+            .split_once('\t')
+            .ok_or_else(|| RepoStateError::Protocol(line.to_string()))?;
+        let mut fields = meta.split(' ');
+        let kind = fields.nth(1).unwrap_or_default();
+        let oid = fields.next().unwrap_or_default();
+        if kind == "blob" {
+            entries.push((path.to_string(), oid.to_string()));
+        }
+    }
+
+    let args = "cat-file --batch".to_string();
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["cat-file", "--batch"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|source| RepoStateError::Spawn { args: args.clone(), source })?;
+
+    let mut stdin = child.stdin.take().expect("piped stdin");
+    let oids: String = entries.iter().map(|(_, oid)| format!("{oid}\n")).collect();
+    let writer = std::thread::spawn(move || stdin.write_all(oids.as_bytes()));
+
+    let mut reader = BufReader::new(child.stdout.take().expect("piped stdout"));
+    let mut leaves = Vec::with_capacity(entries.len());
+    for (path, oid) in entries {
+        let mut header = String::new();
+        reader
+            .read_line(&mut header)
+            .map_err(|source| RepoStateError::Spawn { args: args.clone(), source })?;
+        // "<oid> blob <size>"
+        let size: usize = header
+            .trim_end()
+            .strip_prefix(&format!("{oid} blob "))
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| RepoStateError::Protocol(header.clone()))?;
+        let mut content = vec![0u8; size + 1];
+        reader
+            .read_exact(&mut content)
+            .map_err(|source| RepoStateError::Spawn { args: args.clone(), source })?;
+        content.truncate(size);
+        leaves.push((path, content));
+    }
+
+    writer
+        .join()
+        .map_err(|_| RepoStateError::Protocol("cat-file writer panicked".into()))?
+        .map_err(|source| RepoStateError::Spawn { args: args.clone(), source })?;
+    let status = child
+        .wait()
+        .map_err(|source| RepoStateError::Spawn { args: args.clone(), source })?;
+    if !status.success() {
+        return Err(RepoStateError::Git { args, stderr: format!("exit status {status}") });
+    }
+    Ok(leaves)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn git_ok(repo: &Path, args: &[&str]) {
+        let mut full = vec!["-c", "user.name=t", "-c", "user.email=t@t", "-c", "commit.gpgsign=false"];
+        full.extend_from_slice(args);
+        git(repo, &full).unwrap();
+    }
+
+    #[test]
+    fn test_merkle_root_is_order_independent() {
+        let a = merkle_root(vec![("a".into(), b"1".to_vec()), ("b".into(), b"2".to_vec()), ("c".into(), b"3".to_vec())]);
+        let b = merkle_root(vec![("c".into(), b"3".to_vec()), ("a".into(), b"1".to_vec()), ("b".into(), b"2".to_vec())]);
+        assert_eq!(a, b);
+        assert_ne!(a, merkle_root(vec![("a".into(), b"1".to_vec()), ("b".into(), b"2".to_vec())]));
+        assert_eq!(merkle_root(Vec::new()), hex::encode(digest(&SHA512, b"")));
+    }
+
+    #[test]
+    fn test_worktree_matches_head_until_modified() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path();
+        git_ok(repo, &["init", "-q"]);
+        fs::create_dir_all(repo.join("src")).unwrap();
+        fs::write(repo.join("src/lib.rs"), "pub fn f() {}\n").unwrap();
+        fs::write(repo.join("README.md"), "eco\n").unwrap();
+        git_ok(repo, &["add", "-A"]);
+        git_ok(repo, &["commit", "-q", "-m", "init"]);
+
+        let head_ref = head_ref(repo).unwrap();
+        assert_eq!(head_ref.commit.len(), 40);
+        assert_eq!(head_ref.to_string(), format!("{}@{}", head_ref.branch, head_ref.commit));
+        let dir_name = repo.canonicalize().unwrap().file_name().unwrap().to_string_lossy().into_owned();
+        assert_eq!(repo_name(repo).unwrap(), dir_name);
+        git_ok(repo, &["remote", "add", "origin", "git@github.com:Doctor0Evil/Eco-Sys.git"]);
+        assert_eq!(repo_name(repo).unwrap(), "Eco-Sys");
+
+        let (head, count) = compute_repo_digest(repo, TreeSource::Head, &[]).unwrap();
+        assert_eq!(count, 2);
+        let result =
+            validate_repo_state(repo, &format!("0x{}", head.to_uppercase()), TreeSource::Worktree, &[]).unwrap();
+        assert!(result.passed());
+        assert_eq!(result.file_count, 2);
+
+        fs::write(repo.join("README.md"), "eco, edited\n").unwrap();
+        let result = validate_repo_state(repo, &head, TreeSource::Worktree, &[]).unwrap();
+        assert_eq!(result.status, RepoValidationStatus::Mismatch);
+        assert!(validate_repo_state(repo, &head, TreeSource::Head, &[]).unwrap().passed());
+
+        // Excluded paths may change without affecting the digest.
+        let exclude = [PathBuf::from("README.md")];
+        let (stored, count) = compute_repo_digest(repo, TreeSource::Head, &exclude).unwrap();
+        assert_eq!(count, 1);
+        assert!(validate_repo_state(repo, &stored, TreeSource::Worktree, &exclude).unwrap().passed());
+        fs::write(repo.join("src/lib.rs"), "pub fn g() {}\n").unwrap();
+        assert!(!validate_repo_state(repo, &stored, TreeSource::Worktree, &exclude).unwrap().passed());
+    }
+
+    #[test]
+    fn test_not_a_repository() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(matches!(
+            validate_repo_state(dir.path(), "00", TreeSource::Worktree, &[]),
+            Err(RepoStateError::Git { .. })
+        ));
+    }
+}
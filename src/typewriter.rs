@@ -0,0 +1,649 @@
+use chrono::Utc;
+use serde::{Serialize, Deserialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::canonical::{canonical_digest, canonical_timestamp};
+use crate::aln_anchor::{AnchorError, AnchorWriter, JournalHead};
+use crate::attestation::{AttestationError, SampleAttestation, SampleSigner, SignedEnergySample, TrustedSigner};
+use crate::eco_core::EnergySample;
+use crate::energy_ledger::{windows_signing_payload, EnergyWindow, LedgerTotals};
+use crate::fs_util::write_atomic;
+
+pub const DEFAULT_JOURNAL_PATH: &str = "data-lake/eco-sys/typewriter-journal.json";
+
+#[derive(Debug, Error)]
+pub enum JournalError {
+    #[error("io error on {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("malformed journal {path}: {source}")]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("serialization error: {0}")]
+    Serialize(#[from] serde_json::Error),
+    #[error("anchor check failed: {0}")]
+    Anchor(#[from] AnchorError),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JournalAuthor {
+    pub name: String,
+    pub did: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alternate_did: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub erc20_address: Option<String>,
+    /// Fields this version does not know, kept so a rewrite does not drop them.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JournalSession {
+    pub session_id: String,
+    pub trigger: String,
+    pub orchestrator: String,
+    pub brain: String,
+    /// Fields this version does not know, kept so a rewrite does not drop them.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Energy figures as recorded in a journal commit entry.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JournalEnergySample {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    pub baseline_x_mwz: f64,
+    pub baseline_y_mwz: f64,
+    pub target_utilization: f64,
+    pub computed_efficiency_mwz: f64,
+    /// Fields this version does not know, kept so a rewrite does not drop them.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl From<&EnergySample> for JournalEnergySample {
+    fn from(sample: &EnergySample) -> Self {
+        JournalEnergySample {
+            timestamp: Some(sample.timestamp.clone()),
+            baseline_x_mwz: sample.baseline_x_mwz,
+            baseline_y_mwz: sample.baseline_y_mwz,
+            target_utilization: sample.target_utilization,
+            computed_efficiency_mwz: sample.compute_efficiency(),
+            extra: Map::new(),
+        }
+    }
+}
+
+impl JournalEnergySample {
+    /// Rebuild the exact sample that was signed; legacy entries without a timestamp yield `None`.
+    pub fn to_sample(&self) -> Option<EnergySample> {
+        Some(EnergySample {
+            timestamp: self.timestamp.clone()?,
+            baseline_x_mwz: self.baseline_x_mwz,
+            baseline_y_mwz: self.baseline_y_mwz,
+            target_utilization: self.target_utilization,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JournalCommit {
+    pub commit_id: String,
+    pub timestamp: String,
+    pub repo: String,
+    pub branch: String,
+    #[serde(default)]
+    pub files_modified: Vec<String>,
+    pub eco_proof_hash: String,
+    pub energy_sample: JournalEnergySample,
+    pub validation_state: String,
+    pub authorship_attestation: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attestation: Option<SampleAttestation>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub energy_windows: Vec<EnergyWindow>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub energy_totals: Option<LedgerTotals>,
+    /// Signature over `windows_signing_payload(energy_windows, energy_totals)`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub energy_windows_attestation: Option<SampleAttestation>,
+    /// Fields this version does not know, kept so a rewrite does not drop them.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl JournalCommit {
+    /// Build a commit entry whose `eco_proof_hash` is the sample's `sign_sample` digest.
+    ///
+    /// The entry carries no attestation, so it is recorded as unsigned and unverified.
+    pub fn from_sample(commit_id: &str, repo: &str, branch: &str, sample: &EnergySample) -> Self {
+        JournalCommit {
+            commit_id: commit_id.to_string(),
+            timestamp: canonical_timestamp(Utc::now()),
+            repo: repo.to_string(),
+            branch: branch.to_string(),
+            files_modified: Vec::new(),
+            eco_proof_hash: sample.sign_sample(),
+            energy_sample: JournalEnergySample::from(sample),
+            validation_state: "unverified".to_string(),
+            authorship_attestation: "unsigned".to_string(),
+            attestation: None,
+            energy_windows: Vec::new(),
+            energy_totals: None,
+            energy_windows_attestation: None,
+            extra: Map::new(),
+        }
+    }
+
+    /// Attach integrated ledger windows, signed by `signer`, so the entry
+    /// reflects real intervals that cannot be edited afterwards.
+    pub fn with_energy_windows(mut self, windows: Vec<EnergyWindow>, totals: LedgerTotals, signer: &SampleSigner) -> Self {
+        self.energy_windows_attestation = Some(signer.sign_payload(&windows_signing_payload(&windows, &totals)));
+        self.energy_windows = windows;
+        self.energy_totals = Some(totals);
+        self
+    }
+
+    /// Like `from_sample`, but also records the Ed25519 signature and key id,
+    /// once it verifies against `trusted`.
+    pub fn from_signed(
+        commit_id: &str,
+        repo: &str,
+        branch: &str,
+        signed: &SignedEnergySample,
+        trusted: &TrustedSigner,
+    ) -> Result<Self, AttestationError> {
+        signed.verify(trusted)?;
+        Ok(JournalCommit {
+            validation_state: "verified".to_string(),
+            authorship_attestation: "ed25519-verified".to_string(),
+            attestation: Some(signed.attestation.clone()),
+            ..Self::from_sample(commit_id, repo, branch, &signed.sample)
+        })
+    }
+
+    /// Verify the stored attestations against the recorded sample, ledger
+    /// windows and the trusted signer; `Ok(false)` if unsigned. Ledger windows
+    /// must always carry a signature.
+    pub fn verify_attestation(&self, trusted: &TrustedSigner) -> Result<bool, AttestationError> {
+        if let Some(totals) = &self.energy_totals {
+            let attestation = self.energy_windows_attestation.as_ref().ok_or(AttestationError::Unsigned("energy windows"))?;
+            attestation.verify_payload(&windows_signing_payload(&self.energy_windows, totals), trusted)?;
+        } else if !self.energy_windows.is_empty() {
+            return Err(AttestationError::Unsigned("energy windows"));
+        }
+        let (Some(attestation), Some(sample)) = (&self.attestation, self.energy_sample.to_sample())
+        else {
+            return Ok(false);
+        };
+        attestation.verify(&sample, trusted)?;
+        Ok(true)
+    }
+}
+
+/// One link of the provenance chain. `prev_hash` is the SHA-512 of the preceding
+/// entry; it is absent only on the genesis entry.
+///
+/// `consensus_seal` is only ever read back from entries sealed by the ledger;
+/// entries appended here carry none.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProvenanceEntry {
+    pub ledger: String,
+    pub node: String,
+    pub anchor_hash: String,
+    pub proof_format: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub consensus_seal: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+    /// Fields this version does not know, kept so a rewrite does not drop them
+    /// (they are part of `entry_hash`).
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl ProvenanceEntry {
+    /// SHA-512 over the canonical encoding of the entry, including its own `prev_hash`.
+    pub fn entry_hash(&self) -> Result<String, JournalError> {
+        Ok(canonical_digest(self)?)
+    }
+}
+
+/// First defect found while walking a provenance chain.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChainFault {
+    /// A non-genesis entry carries no `prev_hash`.
+    MissingLink { index: usize },
+    /// `prev_hash` points at an entry other than its predecessor.
+    Reordered { index: usize, links_to: usize },
+    /// `prev_hash` matches no entry in the chain.
+    BrokenLink { index: usize, expected: String, found: String },
+    /// A signed commit entry whose Ed25519 attestation does not verify.
+    BadAttestation { commit: usize, reason: String },
+    /// The journal author is not the DID its signatures are trusted for.
+    UntrustedAuthor { did: String, expected: String },
+    /// An anchor pins a chain state that the entry recording it does not follow.
+    AnchorMismatch { index: usize, anchor_hash: String },
+    /// An anchor pins the chain but no entry records it: the tail was cut.
+    MissingAnchoredEntry { anchor_hash: String, entries: usize },
+}
+
+impl std::fmt::Display for ChainFault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChainFault::MissingLink { index } => {
+                write!(f, "entry {index} has no prev_hash")
+            }
+            ChainFault::Reordered { index, links_to } => {
+                write!(f, "entry {index} links to entry {links_to} instead of entry {}", index - 1)
+            }
+            ChainFault::BrokenLink { index, expected, found } => {
+                write!(f, "entry {index} prev_hash mismatch: expected {expected}, found {found}")
+            }
+            ChainFault::BadAttestation { commit, reason } => {
+                write!(f, "commit {commit} attestation invalid: {reason}")
+            }
+            ChainFault::UntrustedAuthor { did, expected } => {
+                write!(f, "journal author {did} is not the trusted signer {expected}")
+            }
+            ChainFault::AnchorMismatch { index, anchor_hash } => {
+                write!(f, "entry {index} records anchor {anchor_hash}, which pins a different chain")
+            }
+            ChainFault::MissingAnchoredEntry { anchor_hash, entries } => {
+                write!(f, "anchor {anchor_hash} pins {entries} prior entries but the chain does not record it")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainReport {
+    pub entries: usize,
+    pub fault: Option<ChainFault>,
+}
+
+impl ChainReport {
+    pub fn is_intact(&self) -> bool {
+        self.fault.is_none()
+    }
+}
+
+/// Walk the chain front to back and report the first broken or reordered link.
+pub fn verify_chain(chain: &[ProvenanceEntry]) -> Result<ChainReport, JournalError> {
+    let hashes = chain
+        .iter()
+        .map(ProvenanceEntry::entry_hash)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let fault = chain.iter().enumerate().skip(1).find_map(|(index, entry)| {
+        let expected = &hashes[index - 1];
+        match &entry.prev_hash {
+            None => Some(ChainFault::MissingLink { index }),
+            Some(found) if found == expected => None,
+            Some(found) => match hashes.iter().position(|h| h == found) {
+                Some(links_to) => Some(ChainFault::Reordered { index, links_to }),
+                None => Some(ChainFault::BrokenLink {
+                    index,
+                    expected: expected.clone(),
+                    found: found.clone(),
+                }),
+            },
+        }
+    });
+
+    Ok(ChainReport { entries: chain.len(), fault })
+}
+
+/// Check the chain against every indexed anchor that pins a journal head.
+///
+/// The entry recording an anchor must sit right after the state the anchor
+/// pinned, and every such anchor must be recorded, so cutting the tail or
+/// rewriting the chain end to end no longer verifies.
+pub fn verify_chain_anchors(
+    chain: &[ProvenanceEntry],
+    anchors: &AnchorWriter,
+) -> Result<Option<ChainFault>, JournalError> {
+    let hashes = chain
+        .iter()
+        .map(ProvenanceEntry::entry_hash)
+        .collect::<Result<Vec<_>, _>>()?;
+    for entry in anchors.index()?.anchors {
+        let Some(head) = anchors.read(&entry.anchor_hash)?.journal_head else { continue };
+        let Some(index) = chain.iter().position(|e| e.anchor_hash == entry.anchor_hash) else {
+            return Ok(Some(ChainFault::MissingAnchoredEntry {
+                anchor_hash: entry.anchor_hash,
+                entries: head.entries,
+            }));
+        };
+        let pinned = index.checked_sub(1).map(|i| hashes[i].clone());
+        if head.entries != index || head.head_hash != pinned {
+            return Ok(Some(ChainFault::AnchorMismatch { index, anchor_hash: entry.anchor_hash }));
+        }
+    }
+    Ok(None)
+}
+
+/// Load a journal from disk and verify offline its provenance chain, its
+/// author and commit signatures against `trusted`, and the chain against
+/// `anchors` when given.
+pub fn verify_journal(
+    path: impl AsRef<Path>,
+    trusted: &TrustedSigner,
+    anchors: Option<&AnchorWriter>,
+) -> Result<ChainReport, JournalError> {
+    TypewriterJournal::load(path.as_ref())?.verify(trusted, anchors)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JournalCompliance {
+    pub nonfiction_enforcement: bool,
+    pub energy_aware_scheduling: bool,
+    pub tamper_evident_logging: bool,
+    /// Fields this version does not know, kept so a rewrite does not drop them.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// On-disk layout of `typewriter-journal.json`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JournalRecord {
+    pub journal_version: String,
+    pub framework: String,
+    pub created_at: String,
+    pub author: JournalAuthor,
+    pub session: JournalSession,
+    #[serde(default)]
+    pub commits: Vec<JournalCommit>,
+    #[serde(default)]
+    pub provenance_chain: Vec<ProvenanceEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_lake_archive: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compliance: Option<JournalCompliance>,
+    /// Fields this version does not know, kept so a rewrite does not drop them.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Typewriter journal bound to a file path; every `save` replaces the file atomically.
+#[derive(Debug, Clone)]
+pub struct TypewriterJournal {
+    path: PathBuf,
+    record: JournalRecord,
+}
+
+impl TypewriterJournal {
+    pub fn new(path: impl Into<PathBuf>, record: JournalRecord) -> Self {
+        TypewriterJournal { path: path.into(), record }
+    }
+
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, JournalError> {
+        let path = path.into();
+        let data = fs::read_to_string(&path).map_err(|source| JournalError::Io {
+            path: path.clone(),
+            source,
+        })?;
+        let record = serde_json::from_str(&data).map_err(|source| JournalError::Parse {
+            path: path.clone(),
+            source,
+        })?;
+        Ok(TypewriterJournal { path, record })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn record(&self) -> &JournalRecord {
+        &self.record
+    }
+
+    /// Current chain state, for pinning in the next ALN anchor.
+    pub fn chain_head(&self) -> Result<JournalHead, JournalError> {
+        let chain = &self.record.provenance_chain;
+        Ok(JournalHead {
+            entries: chain.len(),
+            head_hash: chain.last().map(ProvenanceEntry::entry_hash).transpose()?,
+        })
+    }
+
+    /// Verify this journal as loaded; see [`verify_journal`].
+    pub fn verify(&self, trusted: &TrustedSigner, anchors: Option<&AnchorWriter>) -> Result<ChainReport, JournalError> {
+        let record = self.record();
+        let mut report = verify_chain(&record.provenance_chain)?;
+        if let (None, Some(anchors)) = (&report.fault, anchors) {
+            report.fault = verify_chain_anchors(&record.provenance_chain, anchors)?;
+        }
+        if report.fault.is_none() && record.author.did != trusted.did {
+            report.fault = Some(ChainFault::UntrustedAuthor {
+                did: record.author.did.clone(),
+                expected: trusted.did.clone(),
+            });
+        }
+        if report.fault.is_none() {
+            report.fault = record.commits.iter().enumerate().find_map(|(commit, entry)| {
+                entry.verify_attestation(trusted).err().map(|e| ChainFault::BadAttestation {
+                    commit,
+                    reason: e.to_string(),
+                })
+            });
+        }
+        Ok(report)
+    }
+
+    pub fn append_commit(&mut self, commit: JournalCommit) {
+        self.record.commits.push(commit);
+    }
+
+    /// Append a provenance entry linked to the current chain head.
+    pub fn append_provenance(
+        &mut self,
+        ledger: &str,
+        node: &str,
+        anchor_hash: &str,
+        proof_format: &str,
+    ) -> Result<&ProvenanceEntry, JournalError> {
+        let prev_hash = match self.record.provenance_chain.last() {
+            Some(head) => Some(head.entry_hash()?),
+            None => None,
+        };
+        self.record.provenance_chain.push(ProvenanceEntry {
+            ledger: ledger.to_string(),
+            node: node.to_string(),
+            anchor_hash: anchor_hash.to_string(),
+            proof_format: proof_format.to_string(),
+            consensus_seal: None,
+            prev_hash,
+            extra: Map::new(),
+        });
+        Ok(self.record.provenance_chain.last().unwrap())
+    }
+
+    /// Atomically replace the journal file with the current record.
+    pub fn save(&self) -> Result<(), JournalError> {
+        let mut encoded = serde_json::to_vec_pretty(&self.record)?;
+        encoded.push(b'\n');
+        write_atomic(&self.path, &encoded).map_err(|source| JournalError::Io {
+            path: self.path.clone(),
+            source,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("journal.json");
+        let mut raw: Value = serde_json::from_str(&fs::read_to_string(DEFAULT_JOURNAL_PATH).unwrap()).unwrap();
+        raw["retention_days"] = Value::from(365);
+        raw["commits"][0]["reviewer"] = Value::from("ops-audit");
+        raw["provenance_chain"][0]["witness"] = Value::from("node-7");
+        fs::write(&path, serde_json::to_vec(&raw).unwrap()).unwrap();
+
+        let mut journal = TypewriterJournal::load(&path).unwrap();
+        let before = journal.record().commits.len();
+        let sample = EnergySample::new(1200.0, 900.0, 0.7);
+        journal.append_commit(JournalCommit::from_sample("test-commit", "Eco-Sys", "main", &sample));
+        journal.save().unwrap();
+
+        let reloaded = TypewriterJournal::load(&path).unwrap();
+        assert_eq!(reloaded.record().commits.len(), before + 1);
+        let last = reloaded.record().commits.last().unwrap();
+        assert_eq!(last.eco_proof_hash, sample.sign_sample());
+        assert_eq!(last.energy_sample.computed_efficiency_mwz, 735.0);
+        assert_eq!((last.validation_state.as_str(), last.authorship_attestation.as_str()), ("unverified", "unsigned"));
+        assert_eq!(reloaded.record().author, journal.record().author);
+        // Unknown fields survive the rewrite.
+        assert_eq!(reloaded.record().extra["retention_days"], 365);
+        assert_eq!(reloaded.record().commits[0].extra["reviewer"], "ops-audit");
+        let genesis = &reloaded.record().provenance_chain[0];
+        assert_eq!(genesis.extra["witness"], "node-7");
+        assert_eq!(genesis.entry_hash().unwrap(), canonical_digest(&raw["provenance_chain"][0]).unwrap());
+    }
+
+    fn chained_journal(links: usize) -> TypewriterJournal {
+        let mut journal = TypewriterJournal::load(DEFAULT_JOURNAL_PATH).unwrap();
+        for i in 0..links {
+            journal
+                .append_provenance("Googolswarm", "node-test", &format!("0x{i:02x}"), "ALN-MultiSig-v2")
+                .unwrap();
+        }
+        journal
+    }
+
+    #[test]
+    fn test_chain_intact() {
+        let journal = chained_journal(3);
+        let report = verify_chain(&journal.record().provenance_chain).unwrap();
+        assert!(report.is_intact());
+        assert_eq!(report.entries, journal.record().provenance_chain.len());
+    }
+
+    #[test]
+    fn test_chain_detects_tamper_and_reorder() {
+        let journal = chained_journal(3);
+
+        let mut tampered = journal.record().provenance_chain.clone();
+        tampered[1].anchor_hash = "0xdeadbeef".to_string();
+        let report = verify_chain(&tampered).unwrap();
+        assert!(matches!(report.fault, Some(ChainFault::BrokenLink { index: 2, .. })));
+
+        let mut reordered = journal.record().provenance_chain.clone();
+        reordered.swap(2, 3);
+        let report = verify_chain(&reordered).unwrap();
+        assert!(matches!(report.fault, Some(ChainFault::Reordered { index: 2, links_to: 3 })));
+    }
+
+    #[test]
+    fn test_signed_commit_verifies_offline() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("journal.json");
+        let signer = crate::attestation::SampleSigner::generate_pkcs8(
+            dir.path().join("signer.pk8"),
+            "bostrom18sd2ujv24ual9c9pshtxys6j8knh6xaead9ye7",
+        )
+        .unwrap();
+
+        let mut journal = chained_journal(0);
+        journal.path = path.clone();
+        let signed = signer.sign(EnergySample::new(1200.0, 900.0, 0.7));
+        let mut ledger = crate::energy_ledger::EnergyLedger::new(3600).unwrap();
+        ledger.ingest("node-a", &signed.sample).unwrap();
+        let trusted = TrustedSigner::new(&journal.record().author.did, vec![signer.public_key_hex()]);
+        let unpinned = TrustedSigner::new(&trusted.did, Vec::new());
+        assert!(JournalCommit::from_signed("signed", "Eco-Sys", "main", &signed, &unpinned).is_err());
+        let commit = JournalCommit::from_signed("signed", "Eco-Sys", "main", &signed, &trusted)
+            .unwrap()
+            .with_energy_windows(ledger.windows(), ledger.totals(), &signer);
+        assert_eq!(commit.authorship_attestation, "ed25519-verified");
+        journal.append_commit(commit);
+        journal.save().unwrap();
+        assert!(verify_journal(&path, &trusted, None).unwrap().is_intact());
+
+        // Nothing pinned: the same self-consistent signature is not trusted.
+        let report = verify_journal(&path, &unpinned, None).unwrap();
+        assert!(matches!(report.fault, Some(ChainFault::BadAttestation { .. })));
+
+        let stranger = TrustedSigner::new("bostrom1other", trusted.public_keys.clone());
+        let report = verify_journal(&path, &stranger, None).unwrap();
+        assert!(matches!(report.fault, Some(ChainFault::UntrustedAuthor { .. })));
+
+        // Edited ledger windows no longer match their signature.
+        let mut windows_edited = journal.clone();
+        windows_edited.record.commits.last_mut().unwrap().energy_windows[0].machine_id = "node-z".to_string();
+        windows_edited.save().unwrap();
+        let report = verify_journal(&path, &trusted, None).unwrap();
+        assert!(matches!(report.fault, Some(ChainFault::BadAttestation { .. })));
+
+        journal.record.commits.last_mut().unwrap().energy_sample.baseline_x_mwz = 1300.0;
+        journal.save().unwrap();
+        let report = verify_journal(&path, &trusted, None).unwrap();
+        assert!(matches!(report.fault, Some(ChainFault::BadAttestation { .. })));
+    }
+
+    #[test]
+    fn test_unindexed_anchor_is_not_checked() {
+        let dir = tempfile::tempdir().unwrap();
+        let anchors = AnchorWriter::new(dir.path().join("anchors"));
+        let journal = chained_journal(0);
+        let record = crate::aln_anchor::AnchorRecord::new("state", "ab", "did:x", None, Utc::now())
+            .with_journal_head(journal.chain_head().unwrap());
+
+        // A run that stops before its journal is saved leaves only an unindexed file.
+        anchors.write_unindexed(&record).unwrap();
+        assert!(anchors.index().unwrap().anchors.is_empty());
+        assert_eq!(verify_chain_anchors(&journal.record().provenance_chain, &anchors).unwrap(), None);
+
+        anchors.register(&record).unwrap();
+        let fault = verify_chain_anchors(&journal.record().provenance_chain, &anchors).unwrap();
+        assert!(matches!(fault, Some(ChainFault::MissingAnchoredEntry { .. })));
+    }
+
+    #[test]
+    fn test_anchors_detect_truncation_and_rewrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let anchors = AnchorWriter::new(dir.path().join("anchors"));
+        let mut journal = chained_journal(0);
+        for i in 0..3 {
+            let record = crate::aln_anchor::AnchorRecord::new(&format!("state-{i}"), "ab", "did:x", None, Utc::now())
+                .with_journal_head(journal.chain_head().unwrap());
+            let hash = anchors.write(&record).unwrap();
+            journal.append_provenance("Googolswarm", "node-test", &hash, "ALN-MultiSig-v2").unwrap();
+        }
+        let chain = journal.record().provenance_chain.clone();
+        assert_eq!(verify_chain_anchors(&chain, &anchors).unwrap(), None);
+        assert!(chain[1..].iter().all(|e| e.consensus_seal.is_none()));
+
+        // Dropping the newest entry still leaves a well-linked chain.
+        let truncated = &chain[..chain.len() - 1];
+        assert!(verify_chain(truncated).unwrap().is_intact());
+        let fault = verify_chain_anchors(truncated, &anchors).unwrap();
+        assert!(matches!(fault, Some(ChainFault::MissingAnchoredEntry { entries: 3, .. })));
+
+        // So does relinking a rewritten chain from the genesis entry on.
+        let mut rewritten = chained_journal(0);
+        rewritten.record.provenance_chain[0].node = "forged-node".to_string();
+        for e in &chain[1..] {
+            rewritten.append_provenance(&e.ledger, &e.node, &e.anchor_hash, &e.proof_format).unwrap();
+        }
+        let rewritten = &rewritten.record().provenance_chain;
+        assert!(verify_chain(rewritten).unwrap().is_intact());
+        let fault = verify_chain_anchors(rewritten, &anchors).unwrap();
+        assert!(matches!(fault, Some(ChainFault::AnchorMismatch { index: 1, .. })));
+    }
+}